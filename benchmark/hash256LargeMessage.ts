@@ -0,0 +1,23 @@
+declare const Benchmark: any;
+
+//Size of the message used to measure throughput for large documents
+const MESSAGE_SIZE = 4 * 1024 * 1024;
+
+export default (Recrypt: typeof import("../lib/Api256Shim"), logResult: (results: string) => void) => {
+    const api = new Recrypt.Api256();
+    const message = new Uint8Array(MESSAGE_SIZE).map((_, i) => i % 256);
+
+    return new Benchmark("hash256 (4MB message)", {
+        fn: () => {
+            api.hash256(message);
+        },
+        onError: (err: Error) => {
+            console.log(err);
+        },
+        onComplete: (result: any) => {
+            const resultString = result.currentTarget.toString();
+            logResult(resultString);
+            console.log(result.currentTarget.toString());
+        },
+    });
+};
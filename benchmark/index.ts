@@ -10,6 +10,10 @@ import transformLevelTwo from "./transformLevelTwo";
 import decryptLevelZero from "./decryptLevelZero";
 import decryptLevelOne from "./decryptLevelOne";
 import decryptLevelTwo from "./decryptLevelTwo";
+import ed25519SignLargeMessage from "./ed25519SignLargeMessage";
+import ed25519VerifyLargeMessage from "./ed25519VerifyLargeMessage";
+import hash256LargeMessage from "./hash256LargeMessage";
+import schnorrSignLargeMessage from "./schnorrSignLargeMessage";
 
 const resultsDiv = document.createElement("div");
 document.body.appendChild(resultsDiv);
@@ -38,6 +42,10 @@ import("../lib/Api256Shim").then((Recrypt) => {
     const decryptLevelZeroBenchmark = decryptLevelZero(Recrypt, logBenchmarkResult);
     const decryptLevelOneBenchmark = decryptLevelOne(Recrypt, logBenchmarkResult);
     const decryptLevelTwoBenchmark = decryptLevelTwo(Recrypt, logBenchmarkResult);
+    const ed25519SignLargeMessageBenchmark = ed25519SignLargeMessage(Recrypt, logBenchmarkResult);
+    const ed25519VerifyLargeMessageBenchmark = ed25519VerifyLargeMessage(Recrypt, logBenchmarkResult);
+    const hash256LargeMessageBenchmark = hash256LargeMessage(Recrypt, logBenchmarkResult);
+    const schnorrSignLargeMessageBenchmark = schnorrSignLargeMessage(Recrypt, logBenchmarkResult);
 
     genKeyPairBenchmark.on("complete", () => {
         genEd25519KeyPairbenchmark.run({async: true});
@@ -84,6 +92,22 @@ import("../lib/Api256Shim").then((Recrypt) => {
     });
 
     decryptLevelTwoBenchmark.on("complete", () => {
+        ed25519SignLargeMessageBenchmark.run({async: true});
+    });
+
+    ed25519SignLargeMessageBenchmark.on("complete", () => {
+        ed25519VerifyLargeMessageBenchmark.run({async: true});
+    });
+
+    ed25519VerifyLargeMessageBenchmark.on("complete", () => {
+        hash256LargeMessageBenchmark.run({async: true});
+    });
+
+    hash256LargeMessageBenchmark.on("complete", () => {
+        schnorrSignLargeMessageBenchmark.run({async: true});
+    });
+
+    schnorrSignLargeMessageBenchmark.on("complete", () => {
         logBenchmarkResult("Benchmark Complete");
     });
 
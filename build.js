@@ -45,6 +45,8 @@ fs.writeFileSync("./pkg/.npmignore", "");
 
 //Move our manually written TS types into the distribution folder
 shell.cp("./recrypt_wasm_binding.d.ts", "./pkg");
+//Publish the protobuf schema used by the *Protobuf encode/decode functions
+shell.cp("./proto/recrypt.proto", "./pkg");
 
 //Compile our wasm-bindgen shim from TS to ES6 JS
 shell.exec(
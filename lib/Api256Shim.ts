@@ -1,54 +1,52 @@
-import {EncryptedValue, KeyPair, PrivateKey, PublicKey, SigningKeyPair, TransformBlock, TransformKey} from "../recrypt_wasm_binding";
+import {Bytes, CoseEncrypt, EncryptedValue, EncryptedValueInfo, Identity, Jwk, KeyPair, KeyPairHandle, PasswordUnwrappedKeyHandles, PasswordUnwrappedKeys, PasswordWrapOptions, PublicKey, PublicKeyInput, PublicSigningKey, SigningKeyPair, TransformKey, ValidationDiagnostic} from "../recrypt_wasm_binding";
 import * as Recrypt from "../target/recrypt_wasm_binding";
 
 /**
- * Convert a public key with Uint8Array values into a public key with normal array values. This is used to pass bytes into
- * WASM as it can't deserialize complex objects with Uint8Arrays.
+ * Run the provided function with a wasm class instance for the provided value. Plain objects are converted into a temporary
+ * instance which is freed once the function returns, class instances are passed through untouched.
  */
-const publicKeyBytesToArray = (publicKey: {x: Uint8Array; y: Uint8Array}) => ({
-    x: Array.from(publicKey.x),
-    y: Array.from(publicKey.y),
-});
-
-/**
- * Convert a public key object with array values into a public key object with Uint8Arrays. This is used for data coming out
- * of WASM as it can't serialize objects with Uint8Arrays as values.
- */
-const publicKeyArrayToBytes = (publicKey: {x: number[]; y: number[]}): PublicKey => ({
-    x: new Uint8Array(publicKey.x),
-    y: new Uint8Array(publicKey.y),
-});
+function withWasmValue<W extends {free(): void}, O, R>(value: W | O, wasmClass: Function & {prototype: W; fromObject(obj: O): W}, fn: (wasmValue: W) => R): R {
+    if (value instanceof wasmClass) {
+        return fn(value);
+    }
+    const wasmValue = wasmClass.fromObject(value as O);
+    try {
+        return fn(wasmValue);
+    } finally {
+        wasmValue.free();
+    }
+}
 
 /**
- * Convert the provided TransformKey object with Uint8Array fields into the same object shape with normal array fields
+ * Run the provided function with a handle for the provided key. Key bytes are imported into a temporary handle which is freed once the
+ * function returns, handles are passed through untouched.
  */
-const transformKeyBytesToArray = (transformKey: TransformKey) => ({
-    encryptedTempKey: Array.from(transformKey.encryptedTempKey),
-    ephemeralPublicKey: publicKeyBytesToArray(transformKey.ephemeralPublicKey),
-    hashedTempKey: Array.from(transformKey.hashedTempKey),
-    publicSigningKey: Array.from(transformKey.publicSigningKey),
-    signature: Array.from(transformKey.signature),
-    toPublicKey: publicKeyBytesToArray(transformKey.toPublicKey),
-});
+function withHandle<H extends {free(): void}, R>(key: Bytes | H, handleClass: Function & {prototype: H}, importKey: (key: Bytes) => H, fn: (handle: H) => R): R {
+    if (key instanceof handleClass) {
+        return fn(key);
+    }
+    const handle = importKey(key as Bytes);
+    try {
+        return fn(handle);
+    } finally {
+        handle.free();
+    }
+}
 
 /**
- * Convert the provided TransformKey object with array fields into the same object shape with Uint8Array fields
+ * Convert the provided wasm class instance into its object form and free it.
  */
-const transformKeyArrayToBytes = (transformKey: any): TransformKey => ({
-    toPublicKey: publicKeyArrayToBytes(transformKey.toPublicKey),
-    ephemeralPublicKey: publicKeyArrayToBytes(transformKey.ephemeralPublicKey),
-    encryptedTempKey: new Uint8Array(transformKey.encryptedTempKey),
-    hashedTempKey: new Uint8Array(transformKey.hashedTempKey),
-    publicSigningKey: new Uint8Array(transformKey.publicSigningKey),
-    signature: new Uint8Array(transformKey.signature),
-});
+function intoObject<O>(wasmValue: {free(): void; toObject(): O}): O {
+    try {
+        return wasmValue.toObject();
+    } finally {
+        wasmValue.free();
+    }
+}
 
 /**
- * JS shim that is necessary to convert types between the marshaling layer of Rust. Currently wasm-bindgen doens't support the ability to pass
- * objects with Uint8Array properties (see https://github.com/rustwasm/wasm-bindgen/issues/779). This makes the API for Rust not ideal so we created
- * this wrapper class in order to make the public interface for consumers more reasonable. The shim functions here convert Uint8Arrays that are part
- * of objects going in to normal arrays (which can be successfully serialized) as well as converting array values coming out of Rust from normal arrays
- * into Uint8Arrays.
+ * Typed wrapper around the wasm-bindgen generated Api256 class. The WASM module reads and returns objects with Uint8Array
+ * fields directly, so this class only exists to expose the types from recrypt_wasm_binding.d.ts to consumers.
  */
 export class Api256 {
     private api: Recrypt.Api256;
@@ -58,98 +56,59 @@ export class Api256 {
     }
 
     /**
-     * Map over each incoming transform block and convert the byte values into normal arrays so they can be passed into WASM.
-     */
-    private transformBlocksBytesToArray(transformBlocks: TransformBlock[]) {
-        return transformBlocks.map((block) => ({
-            publicKey: publicKeyBytesToArray(block.publicKey),
-            encryptedTempKey: Array.from(block.encryptedTempKey),
-            randomTransformPublicKey: publicKeyBytesToArray(block.randomTransformPublicKey),
-            randomTransformEncryptedTempKey: Array.from(block.randomTransformEncryptedTempKey),
-        }));
-    }
-
-    /**
-     * Map over each outgoing transform block and convert the normal arrays into Uint8Arrays before passing back out
-     * to the caller.
+     * Generate a Recrypt public and private key pair. Returns results as Uint8Arrays.
      */
-    private transformBlocksArrayToBytes(transformBlocks: any): TransformBlock[] {
-        return transformBlocks.map((block: any) => ({
-            publicKey: publicKeyArrayToBytes(block.publicKey),
-            encryptedTempKey: new Uint8Array(block.encryptedTempKey),
-            randomTransformPublicKey: publicKeyArrayToBytes(block.randomTransformPublicKey),
-            randomTransformEncryptedTempKey: new Uint8Array(block.randomTransformEncryptedTempKey),
-        }));
+    generateKeyPair(): KeyPair {
+        return this.api.generateKeyPair();
     }
 
     /**
-     * Convert an incoming EncryptedValue from Uint8Array values into simple arrays in order to pass it into Rust via wasm-bindgen.
+     * Generate an ed25519 signing key pair.
      */
-    private encryptedValueBytesToArray(encryptedValue: EncryptedValue) {
-        return {
-            ephemeralPublicKey: publicKeyBytesToArray(encryptedValue.ephemeralPublicKey),
-            encryptedMessage: Array.from(encryptedValue.encryptedMessage),
-            authHash: Array.from(encryptedValue.authHash),
-            publicSigningKey: Array.from(encryptedValue.publicSigningKey),
-            signature: Array.from(encryptedValue.signature),
-            transformBlocks: this.transformBlocksBytesToArray(encryptedValue.transformBlocks),
-        };
+    generateEd25519KeyPair(): SigningKeyPair {
+        return this.api.generateEd25519KeyPair();
     }
 
     /**
-     * Convert an EncryptedValue coming from WASM into Uint8Arrays before passing it out to the API consumer.
+     * Derive a Recrypt key pair from the master seed at the provided "/" separated path with HKDF-SHA256. The same seed and path always give
+     * the same key pair.
      */
-    private encryptedValueArrayToBytes(encryptedValue: any): EncryptedValue {
-        return {
-            ephemeralPublicKey: publicKeyArrayToBytes(encryptedValue.ephemeralPublicKey),
-            encryptedMessage: new Uint8Array(encryptedValue.encryptedMessage),
-            authHash: new Uint8Array(encryptedValue.authHash),
-            publicSigningKey: new Uint8Array(encryptedValue.publicSigningKey),
-            signature: new Uint8Array(encryptedValue.signature),
-            transformBlocks: this.transformBlocksArrayToBytes(encryptedValue.transformBlocks),
-        };
+    deriveKeyPair(masterSeed: Bytes, path: string): KeyPair {
+        return this.api.deriveKeyPair(masterSeed, path);
     }
 
     /**
-     * Generate a Recrypt public and private key pair. Returns results as Uint8Arrays.
+     * Derive an ed25519 signing key pair from the master seed at the provided "/" separated path with HKDF-SHA256. Unrelated to the key pair
+     * deriveKeyPair gives for the same path.
      */
-    generateKeyPair(): KeyPair {
-        const {privateKey, publicKey} = this.api.generateKeyPair();
-        return {
-            privateKey: new Uint8Array(privateKey),
-            publicKey: publicKeyArrayToBytes(publicKey),
-        };
-    }
-
-    /**
-     * Generate an ed25519 signing key pair.
-     */
-    generateEd25519KeyPair(): SigningKeyPair {
-        const {privateKey, publicKey} = this.api.generateEd25519KeyPair();
-        return {
-            privateKey: new Uint8Array(privateKey),
-            publicKey: new Uint8Array(publicKey),
-        };
+    deriveSigningKeyPair(masterSeed: Bytes, path: string): SigningKeyPair {
+        return this.api.deriveSigningKeyPair(masterSeed, path);
     }
 
     /**
      * Sign the provided message with the provided ed25519 private key.
      */
-    ed25519Sign(privateSigningKey: Uint8Array, message: Uint8Array): Uint8Array {
+    ed25519Sign(privateSigningKey: Bytes | Recrypt.SigningKeyHandle, message: Bytes): Uint8Array {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            return this.api.ed25519SignWithHandle(privateSigningKey, message);
+        }
         return this.api.ed25519Sign(privateSigningKey, message);
     }
 
     /**
      * Verify that the provided signature matches the provided message and was signed with the private key associated to the provided public signing key
      */
-    ed25519Verify(publicSigningKey: Uint8Array, message: Uint8Array, signature: Uint8Array): boolean {
+    ed25519Verify(publicSigningKey: Bytes, message: Bytes, signature: Bytes): boolean {
         return this.api.ed25519Verify(publicSigningKey, message, signature);
     }
 
     /**
      * Compute an ed25519 public key given its private key.
      */
-    computeEd25519PublicKey(privateSigningKey: Uint8Array): Uint8Array {
+    computeEd25519PublicKey(privateSigningKey: Bytes | Recrypt.SigningKeyHandle): Uint8Array {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            return privateSigningKey.publicKey;
+        }
         return this.api.computeEd25519PublicKey(privateSigningKey);
     }
 
@@ -163,60 +122,133 @@ export class Api256 {
     /**
      * Generate a transform key from the provided private key to the provided public key.
      */
-    generateTransformKey(fromPrivateKey: Uint8Array, toPublicKey: PublicKey, privateSigningKey: Uint8Array): TransformKey {
-        const transformKey = this.api.generateTransformKey(fromPrivateKey, publicKeyBytesToArray(toPublicKey), privateSigningKey);
-        return transformKeyArrayToBytes(transformKey);
+    generateTransformKey(
+        fromPrivateKey: Bytes | Recrypt.PrivateKeyHandle,
+        toPublicKey: PublicKeyInput,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): TransformKey;
+    generateTransformKey(
+        fromPrivateKey: Bytes | Recrypt.PrivateKeyHandle,
+        toPublicKey: Recrypt.WasmPublicKey,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): Recrypt.WasmTransformKey;
+    generateTransformKey(
+        fromPrivateKey: Bytes | Recrypt.PrivateKeyHandle,
+        toPublicKey: PublicKeyInput | Recrypt.WasmPublicKey,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): TransformKey | Recrypt.WasmTransformKey {
+        if (fromPrivateKey instanceof Recrypt.PrivateKeyHandle || privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            const transformKey = withHandle(fromPrivateKey, Recrypt.PrivateKeyHandle, (key) => this.api.importPrivateKey(key), (privateKeyHandle) =>
+                withHandle(privateSigningKey, Recrypt.SigningKeyHandle, (key) => this.api.importSigningKey(key), (signingKeyHandle) =>
+                    withWasmValue(toPublicKey, Recrypt.WasmPublicKey, (wasmPublicKey) =>
+                        this.api.generateTransformKeyWithHandles(privateKeyHandle, wasmPublicKey, signingKeyHandle)
+                    )
+                )
+            );
+            return toPublicKey instanceof Recrypt.WasmPublicKey ? transformKey : intoObject(transformKey);
+        }
+        if (toPublicKey instanceof Recrypt.WasmPublicKey) {
+            return this.api.generateTransformKeyWasm(fromPrivateKey, toPublicKey, privateSigningKey);
+        }
+        return this.api.generateTransformKey(fromPrivateKey, toPublicKey, privateSigningKey);
     }
 
     /**
      * Compute the associated public key for the provided private key bytes.
      */
-    computePublicKey(privateKey: Uint8Array): PublicKey {
-        return publicKeyArrayToBytes(this.api.computePublicKey(privateKey));
+    computePublicKey(privateKey: Bytes | Recrypt.PrivateKeyHandle): PublicKey {
+        if (privateKey instanceof Recrypt.PrivateKeyHandle) {
+            return privateKey.publicKey;
+        }
+        return this.api.computePublicKey(privateKey);
     }
 
     /**
      * Encrypt the provided plaintext to the provided public key. Signs the resulting encrypted value with the provided signing key pair. Returns a
      * complex object of encrypted data which can be directly passed into decrypt.
      */
-    encrypt(plaintext: Uint8Array, toPublicKey: PublicKey, privateSigningKey: Uint8Array): EncryptedValue {
-        const encryptedValue = this.api.encrypt(plaintext, publicKeyBytesToArray(toPublicKey), privateSigningKey);
-        return this.encryptedValueArrayToBytes(encryptedValue);
+    encrypt(plaintext: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): EncryptedValue;
+    encrypt(plaintext: Bytes, toPublicKey: Recrypt.WasmPublicKey, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): Recrypt.WasmEncryptedValue;
+    encrypt(
+        plaintext: Bytes,
+        toPublicKey: PublicKeyInput | Recrypt.WasmPublicKey,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): EncryptedValue | Recrypt.WasmEncryptedValue {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            const encryptedValue = withWasmValue(toPublicKey, Recrypt.WasmPublicKey, (wasmPublicKey) =>
+                this.api.encryptWithHandle(plaintext, wasmPublicKey, privateSigningKey)
+            );
+            return toPublicKey instanceof Recrypt.WasmPublicKey ? encryptedValue : intoObject(encryptedValue);
+        }
+        if (toPublicKey instanceof Recrypt.WasmPublicKey) {
+            return this.api.encryptWasm(plaintext, toPublicKey, privateSigningKey);
+        }
+        return this.api.encrypt(plaintext, toPublicKey, privateSigningKey);
     }
 
     /**
      * Transform the provided EncryptedValue using the provided TransformKey. Signs the resulting encrypted value with the provided signing key pair. Returns
-     * a new EncryptedValue with another level of transformBlocks in it.
+     * a new EncryptedValue with another level of transformBlocks in it. If either argument is a wasm class instance a
+     * WasmEncryptedValue is returned.
      */
-    transform(encryptedValue: EncryptedValue, transformKey: TransformKey, privateSigningKey: Uint8Array): EncryptedValue {
-        const transformedEncryptedValue = this.api.transform(
-            this.encryptedValueBytesToArray(encryptedValue),
-            transformKeyBytesToArray(transformKey),
-            privateSigningKey
+    transform(encryptedValue: EncryptedValue, transformKey: TransformKey, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): EncryptedValue;
+    transform(
+        encryptedValue: EncryptedValue | Recrypt.WasmEncryptedValue,
+        transformKey: TransformKey | Recrypt.WasmTransformKey,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): Recrypt.WasmEncryptedValue;
+    transform(
+        encryptedValue: EncryptedValue | Recrypt.WasmEncryptedValue,
+        transformKey: TransformKey | Recrypt.WasmTransformKey,
+        privateSigningKey: Bytes | Recrypt.SigningKeyHandle
+    ): EncryptedValue | Recrypt.WasmEncryptedValue {
+        const hasWasmValue = encryptedValue instanceof Recrypt.WasmEncryptedValue || transformKey instanceof Recrypt.WasmTransformKey;
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            const transformedValue = withWasmValue(encryptedValue, Recrypt.WasmEncryptedValue, (wasmEncryptedValue) =>
+                withWasmValue(transformKey, Recrypt.WasmTransformKey, (wasmTransformKey) =>
+                    this.api.transformWithHandle(wasmEncryptedValue, wasmTransformKey, privateSigningKey)
+                )
+            );
+            return hasWasmValue ? transformedValue : intoObject(transformedValue);
+        }
+        if (!hasWasmValue) {
+            return this.api.transform(encryptedValue, transformKey, privateSigningKey);
+        }
+        return withWasmValue(encryptedValue, Recrypt.WasmEncryptedValue, (wasmEncryptedValue) =>
+            withWasmValue(transformKey, Recrypt.WasmTransformKey, (wasmTransformKey) =>
+                this.api.transformWasm(wasmEncryptedValue, wasmTransformKey, privateSigningKey)
+            )
         );
-
-        return this.encryptedValueArrayToBytes(transformedEncryptedValue);
     }
 
     /**
      * Decrypt the provided encrypted value using the provided private key and return the decrypted plaintext bytes as a Uint8Array.
      */
-    decrypt(encryptedValue: EncryptedValue, privateKey: Uint8Array): Uint8Array {
-        return this.api.decrypt(this.encryptedValueBytesToArray(encryptedValue), privateKey);
+    decrypt(encryptedValue: EncryptedValue | Recrypt.WasmEncryptedValue, privateKey: Bytes | Recrypt.PrivateKeyHandle): Uint8Array {
+        if (privateKey instanceof Recrypt.PrivateKeyHandle) {
+            return withWasmValue(encryptedValue, Recrypt.WasmEncryptedValue, (wasmEncryptedValue) => this.api.decryptWithHandle(wasmEncryptedValue, privateKey));
+        }
+        if (encryptedValue instanceof Recrypt.WasmEncryptedValue) {
+            return this.api.decryptWasm(encryptedValue, privateKey);
+        }
+        return this.api.decrypt(encryptedValue, privateKey);
     }
 
     /**
      * Derive the 32 byte symmetric key from the provided plaintext.
      */
-    deriveSymmetricKey(plaintext: Uint8Array): Uint8Array {
+    deriveSymmetricKey(plaintext: Bytes): Uint8Array {
         return this.api.deriveSymmetricKey(plaintext);
     }
 
     /**
      * Sign the provided message with the provided keypair using Schnorr signing. Returns a 64 byte signature.
      */
-    schnorrSign(privateKey: Uint8Array, publicKey: PublicKey, message: Uint8Array): Uint8Array {
-        return this.api.schnorrSign(privateKey, publicKeyBytesToArray(publicKey), message);
+    schnorrSign(privateKey: Bytes | Recrypt.PrivateKeyHandle, publicKey: PublicKeyInput, message: Bytes): Uint8Array {
+        if (privateKey instanceof Recrypt.PrivateKeyHandle) {
+            return this.api.schnorrSignWithHandle(privateKey, publicKey, message);
+        }
+        return this.api.schnorrSign(privateKey, publicKey, message);
     }
 
     /**
@@ -224,14 +256,174 @@ export class Api256 {
      * well as the optional augmented private key.
      *
      */
-    schnorrVerify(publicKey: PublicKey, augmentedPrivateKey: Uint8Array | undefined, message: Uint8Array, signature: Uint8Array): boolean {
-        return this.api.schnorrVerify(publicKeyBytesToArray(publicKey), augmentedPrivateKey as Uint8Array, message, signature);
+    schnorrVerify(publicKey: PublicKeyInput, augmentedPrivateKey: Bytes | undefined, message: Bytes, signature: Bytes): boolean {
+        return this.api.schnorrVerify(publicKey, augmentedPrivateKey, message, signature);
+    }
+
+    /**
+     * Encrypt the provided message to the provided public key as a JWE in the compact serialization. The content is encrypted with AES-256-GCM under
+     * a key derived from a fresh plaintext, which is recrypt encrypted to the public key as the JWE Encrypted Key.
+     */
+    encryptJwe(message: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): string {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            return this.api.encryptJweWithHandle(message, toPublicKey, privateSigningKey);
+        }
+        return this.api.encryptJwe(message, toPublicKey, privateSigningKey);
+    }
+
+    /**
+     * Same as encryptJwe, but returns the JWE in the general JSON serialization with a single recipient.
+     */
+    encryptJweJson(message: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): string {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            return this.api.encryptJweJsonWithHandle(message, toPublicKey, privateSigningKey);
+        }
+        return this.api.encryptJweJson(message, toPublicKey, privateSigningKey);
+    }
+
+    /**
+     * Transform the Encrypted Key of the provided JWE with the provided transform key. The content is untouched and the JWE is returned in the
+     * serialization it was provided in.
+     */
+    transformJwe(jwe: string, transformKey: TransformKey, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): string {
+        if (privateSigningKey instanceof Recrypt.SigningKeyHandle) {
+            return this.api.transformJweWithHandle(jwe, transformKey, privateSigningKey);
+        }
+        return this.api.transformJwe(jwe, transformKey, privateSigningKey);
+    }
+
+    /**
+     * Decrypt a JWE produced by encryptJwe or encryptJweJson, and possibly transformed by transformJwe, with the provided private key. Accepts the
+     * compact, general JSON and flattened JSON serializations.
+     */
+    decryptJwe(jwe: string, privateKey: Bytes | Recrypt.PrivateKeyHandle): Uint8Array {
+        if (privateKey instanceof Recrypt.PrivateKeyHandle) {
+            return this.api.decryptJweWithHandle(jwe, privateKey);
+        }
+        return this.api.decryptJwe(jwe, privateKey);
+    }
+
+    /**
+     * Export the provided key pair and signing key pair, with their key IDs and creation time, as one versioned and checksummed JSON bundle.
+     * createdAt is milliseconds since the Unix epoch and defaults to now.
+     */
+    exportIdentity(keyPair: KeyPair, signingKeyPair: SigningKeyPair, createdAt?: number): string {
+        return this.api.exportIdentity(keyPair, signingKeyPair, createdAt);
+    }
+
+    /**
+     * Import a bundle produced by exportIdentity. The checksum, both public keys and both key IDs are verified before anything is returned.
+     */
+    importIdentity(bundle: string): Identity {
+        return this.api.importIdentity(bundle);
+    }
+
+    /**
+     * Import a JWK produced by exportJwk, or an Ed25519 private key JWK from any other tool. Returns a public key, key pair or signing key
+     * pair depending on the JWK, validated the same way as the object forms. The public key of a recrypt private key is recomputed and must
+     * match x and y. Members other than kty, crv, x, y and d are ignored.
+     */
+    importJwk(jwk: Jwk): PublicKey | KeyPair | SigningKeyPair {
+        return this.api.importJwk(jwk);
+    }
+
+    /**
+     * Encrypt the provided private key, and the signing key if one is provided, under a key derived from the password with Argon2id. Returns a
+     * versioned blob which records the Argon2id parameters, so it can be unwrapped with just the password.
+     */
+    wrapPrivateKeyWithPassword(
+        privateKey: Bytes | Recrypt.PrivateKeyHandle,
+        password: string | Bytes,
+        privateSigningKey?: Bytes | Recrypt.SigningKeyHandle,
+        options?: PasswordWrapOptions
+    ): Uint8Array {
+        if (!(privateKey instanceof Recrypt.PrivateKeyHandle || privateSigningKey instanceof Recrypt.SigningKeyHandle)) {
+            return this.api.wrapPrivateKeyWithPassword(privateKey, password, privateSigningKey, options);
+        }
+        return withHandle(privateKey, Recrypt.PrivateKeyHandle, (key) => this.api.importPrivateKey(key), (privateKeyHandle) => {
+            if (privateSigningKey === undefined) {
+                return this.api.wrapPrivateKeyWithPasswordWithHandle(privateKeyHandle, password, options);
+            }
+            return withHandle(privateSigningKey, Recrypt.SigningKeyHandle, (key) => this.api.importSigningKey(key), (signingKeyHandle) =>
+                this.api.wrapPrivateKeyWithPasswordWithHandles(privateKeyHandle, password, signingKeyHandle, options)
+            );
+        });
+    }
+
+    /**
+     * Decrypt a blob produced by wrapPrivateKeyWithPassword with the password. The public key is recomputed from the private key and checked
+     * against the one recorded in the blob.
+     */
+    unwrapPrivateKeyWithPassword(wrappedKey: Bytes, password: string | Bytes): PasswordUnwrappedKeys {
+        return this.api.unwrapPrivateKeyWithPassword(wrappedKey, password);
+    }
+
+    /**
+     * Same as unwrapPrivateKeyWithPassword, but the private key is returned as a PrivateKeyHandle and the signing key, if one was wrapped, as a
+     * SigningKeyHandle, so neither is copied into JS memory.
+     */
+    unwrapPrivateKeyHandleWithPassword(wrappedKey: Bytes, password: string | Bytes): PasswordUnwrappedKeyHandles {
+        return this.api.unwrapPrivateKeyHandleWithPassword(wrappedKey, password);
+    }
+
+    /**
+     * Generate a Recrypt key pair whose private key is kept in WASM memory as a PrivateKeyHandle.
+     */
+    generateKeyPairHandle(): KeyPairHandle {
+        return this.api.generateKeyPairHandle();
+    }
+
+    /**
+     * Move the provided private key bytes into a PrivateKeyHandle. The caller is responsible for clearing their own copy.
+     */
+    importPrivateKey(privateKey: Bytes): Recrypt.PrivateKeyHandle {
+        return this.api.importPrivateKey(privateKey);
+    }
+
+    /**
+     * Export the private key in the provided handle as a compact JWE encrypted to the provided public key. This and wrapPrivateKeyWithPassword
+     * are the only ways to get the key back out of a handle.
+     */
+    wrapPrivateKey(privateKey: Recrypt.PrivateKeyHandle, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | Recrypt.SigningKeyHandle): string {
+        return withHandle(
+            privateSigningKey,
+            Recrypt.SigningKeyHandle,
+            (key) => this.api.importSigningKey(key),
+            (signingKeyHandle) => this.api.wrapPrivateKey(privateKey, toPublicKey, signingKeyHandle)
+        );
+    }
+
+    /**
+     * Decrypt a key wrapped by wrapPrivateKey with the provided private key and return it as a new PrivateKeyHandle.
+     */
+    unwrapPrivateKey(wrappedKey: string, unwrappingKey: Bytes | Recrypt.PrivateKeyHandle): Recrypt.PrivateKeyHandle {
+        return withHandle(
+            unwrappingKey,
+            Recrypt.PrivateKeyHandle,
+            (key) => this.api.importPrivateKey(key),
+            (unwrappingKeyHandle) => this.api.unwrapPrivateKey(wrappedKey, unwrappingKeyHandle)
+        );
+    }
+
+    /**
+     * Generate an Ed25519 signing key pair which is kept in WASM memory as a SigningKeyHandle.
+     */
+    generateSigningKeyHandle(): Recrypt.SigningKeyHandle {
+        return this.api.generateSigningKeyHandle();
+    }
+
+    /**
+     * Parse the provided 64 byte private signing key or 32 byte seed into a SigningKeyHandle. The caller is responsible for clearing their own
+     * copy.
+     */
+    importSigningKey(privateSigningKey: Bytes): Recrypt.SigningKeyHandle {
+        return this.api.importSigningKey(privateSigningKey);
     }
 
     /**
      * SHA256 hash the provided bytes
      */
-    hash256(bytes: Uint8Array): Uint8Array {
+    hash256(bytes: Bytes): Uint8Array {
         return this.api.hash256(bytes);
     }
 }
@@ -239,33 +431,277 @@ export class Api256 {
 /**
  * Convert the provided TransformKey object into a single Uint8Array of bytes in a consistent order
  */
-export const transformKeyToBytes256 = (transformKey: TransformKey): Uint8Array => Recrypt.transformKeyToBytes256(transformKeyBytesToArray(transformKey));
+export const transformKeyToBytes256 = (transformKey: TransformKey): Uint8Array => Recrypt.transformKeyToBytes256(transformKey);
 
 /**
  * Augment the provided transform key object with the provided private key. Returns a new, augmented TransformKey
  */
-export const augmentTransformKey256 = (transformKey: TransformKey, privateKey: PrivateKey): TransformKey =>
-    transformKeyArrayToBytes(Recrypt.augmentTransformKey256(transformKeyBytesToArray(transformKey), privateKey));
+export const augmentTransformKey256 = (transformKey: TransformKey, privateKey: Bytes): TransformKey => Recrypt.augmentTransformKey256(transformKey, privateKey);
 
 /**
  * Augment the provided public key object with another public key object. Returns the augmented PublicKey.
  */
-export const augmentPublicKey256 = (currentPublicKey: PublicKey, otherPublicKey: PublicKey): PublicKey => {
-    const augmentedKey = Recrypt.augmentPublicKey256(publicKeyBytesToArray(currentPublicKey), publicKeyBytesToArray(otherPublicKey));
-    return publicKeyArrayToBytes(augmentedKey);
-};
+export const augmentPublicKey256 = (currentPublicKey: PublicKeyInput, otherPublicKey: PublicKeyInput): PublicKey =>
+    Recrypt.augmentPublicKey256(currentPublicKey, otherPublicKey);
 
 /**
  * Create a new private key by adding together the provided private keys.
  */
-export const addPrivateKeys = (privateKeyA: Uint8Array, privateKeyB: Uint8Array): Uint8Array => Recrypt.addPrivateKeys(privateKeyA, privateKeyB);
+export const addPrivateKeys = (privateKeyA: Bytes, privateKeyB: Bytes): Uint8Array => Recrypt.addPrivateKeys(privateKeyA, privateKeyB);
 
 /**
  * Create a new private key by subtracting the provided private keys.
  */
-export const subtractPrivateKeys = (privateKeyA: Uint8Array, privateKeyB: Uint8Array): Uint8Array => Recrypt.subtractPrivateKeys(privateKeyA, privateKeyB);
+export const subtractPrivateKeys = (privateKeyA: Bytes, privateKeyB: Bytes): Uint8Array => Recrypt.subtractPrivateKeys(privateKeyA, privateKeyB);
+
+/**
+ * Describe the provided encrypted value without decrypting it: the variant, the number of transform hops, the ephemeralPublicKey of the transform
+ * key used for each hop and the public signing key that signed the value.
+ */
+export const inspectEncryptedValue = (encryptedValue: EncryptedValue | Recrypt.WasmEncryptedValue): EncryptedValueInfo =>
+    encryptedValue instanceof Recrypt.WasmEncryptedValue ? encryptedValue.inspect() : Recrypt.inspectEncryptedValue(encryptedValue);
+
+/**
+ * Encode the provided encrypted value into a compact, versioned binary form for storage. Works for both once encrypted and transformed values.
+ */
+export const encodeEncryptedValue = (encryptedValue: EncryptedValue): Uint8Array => Recrypt.encodeEncryptedValue(encryptedValue);
+
+/**
+ * Decode bytes produced by encodeEncryptedValue back into an encrypted value.
+ */
+export const decodeEncryptedValue = (encodedValue: Bytes): EncryptedValue => Recrypt.decodeEncryptedValue(encodedValue);
+
+/**
+ * Encode the provided transform key into a compact, versioned binary form which, unlike transformKeyToBytes256, can be decoded again.
+ */
+export const encodeTransformKey = (transformKey: TransformKey): Uint8Array => Recrypt.encodeTransformKey(transformKey);
+
+/**
+ * Decode bytes produced by encodeTransformKey back into a transform key.
+ */
+export const decodeTransformKey = (encodedKey: Bytes): TransformKey => Recrypt.decodeTransformKey(encodedKey);
+
+/**
+ * Encode the provided encrypted value as an EncryptedValue protobuf message. The schema is published as recrypt.proto.
+ */
+export const encodeEncryptedValueProtobuf = (encryptedValue: EncryptedValue): Uint8Array => Recrypt.encodeEncryptedValueProtobuf(encryptedValue);
+
+/**
+ * Decode an EncryptedValue protobuf message into an encrypted value.
+ */
+export const decodeEncryptedValueProtobuf = (encodedValue: Bytes): EncryptedValue => Recrypt.decodeEncryptedValueProtobuf(encodedValue);
+
+/**
+ * Encode the provided transform key as a TransformKey protobuf message. The schema is published as recrypt.proto.
+ */
+export const encodeTransformKeyProtobuf = (transformKey: TransformKey): Uint8Array => Recrypt.encodeTransformKeyProtobuf(transformKey);
+
+/**
+ * Decode a TransformKey protobuf message into a transform key.
+ */
+export const decodeTransformKeyProtobuf = (encodedKey: Bytes): TransformKey => Recrypt.decodeTransformKeyProtobuf(encodedKey);
+
+/**
+ * Encode the provided public key as a CBOR map with x/y byte strings.
+ */
+export const encodePublicKeyCbor = (publicKey: PublicKeyInput): Uint8Array => Recrypt.encodePublicKeyCbor(publicKey);
+
+/**
+ * Decode a CBOR encoded public key, validating that it is on the curve.
+ */
+export const decodePublicKeyCbor = (encodedKey: Bytes): PublicKey => Recrypt.decodePublicKeyCbor(encodedKey);
+
+/**
+ * Encode the provided encrypted value as a CBOR map with the same keys as the object.
+ */
+export const encodeEncryptedValueCbor = (encryptedValue: EncryptedValue): Uint8Array => Recrypt.encodeEncryptedValueCbor(encryptedValue);
+
+/**
+ * Decode a CBOR encoded encrypted value.
+ */
+export const decodeEncryptedValueCbor = (encodedValue: Bytes): EncryptedValue => Recrypt.decodeEncryptedValueCbor(encodedValue);
+
+/**
+ * Encode the provided transform key as a CBOR map with the same keys as the object.
+ */
+export const encodeTransformKeyCbor = (transformKey: TransformKey): Uint8Array => Recrypt.encodeTransformKeyCbor(transformKey);
+
+/**
+ * Decode a CBOR encoded transform key.
+ */
+export const decodeTransformKeyCbor = (encodedKey: Bytes): TransformKey => Recrypt.decodeTransformKeyCbor(encodedKey);
+
+/**
+ * Encode the provided signing key pair as a CBOR map with privateKey/publicKey byte strings.
+ */
+export const encodeSigningKeyPairCbor = (signingKeyPair: SigningKeyPair): Uint8Array => Recrypt.encodeSigningKeyPairCbor(signingKeyPair);
+
+/**
+ * Decode a CBOR encoded signing key pair, verifying that the public key matches the private key.
+ */
+export const decodeSigningKeyPairCbor = (encodedKeyPair: Bytes): SigningKeyPair => Recrypt.decodeSigningKeyPairCbor(encodedKeyPair);
+
+/**
+ * The additional data content must be AES-256-GCM encrypted with before being wrapped by encodeCoseEncrypt.
+ */
+export const coseEncryptAad = (): Uint8Array => Recrypt.coseEncryptAad();
+
+/**
+ * Wrap AES-256-GCM encrypted content in a COSE_Encrypt envelope. The recrypt encrypted DEK goes in the recipient structure, with its signature and
+ * public signing key in the recipient headers. The content key should be derived from the DEK's plaintext with deriveSymmetricKey.
+ */
+export const encodeCoseEncrypt = (encryptedDek: EncryptedValue, ciphertext: Bytes, iv: Bytes): Uint8Array => Recrypt.encodeCoseEncrypt(encryptedDek, ciphertext, iv);
+
+/**
+ * Unwrap a COSE_Encrypt envelope produced by encodeCoseEncrypt.
+ */
+export const decodeCoseEncrypt = (envelope: Bytes): CoseEncrypt => Recrypt.decodeCoseEncrypt(envelope);
+
+/**
+ * Serialize the provided public key as versioned JSON with unpadded base64url coordinates.
+ */
+export const publicKeyToJson = (publicKey: PublicKeyInput): string => Recrypt.publicKeyToJson(publicKey);
+
+/**
+ * Parse JSON produced by publicKeyToJson. Throws on unknown or missing fields, invalid base64url, an unsupported version or a public key which is not on the curve.
+ */
+export const publicKeyFromJson = (json: string): PublicKey => Recrypt.publicKeyFromJson(json);
+
+/**
+ * Serialize the provided key pair as versioned JSON with unpadded base64url fields.
+ */
+export const keyPairToJson = (keyPair: KeyPair): string => Recrypt.keyPairToJson(keyPair);
+
+/**
+ * Parse JSON produced by keyPairToJson.
+ */
+export const keyPairFromJson = (json: string): KeyPair => Recrypt.keyPairFromJson(json);
+
+/**
+ * Serialize the provided Ed25519 signing key pair as versioned JSON with unpadded base64url fields.
+ */
+export const signingKeyPairToJson = (signingKeyPair: SigningKeyPair): string => Recrypt.signingKeyPairToJson(signingKeyPair);
+
+/**
+ * Parse JSON produced by signingKeyPairToJson. Throws if the public key doesn't match the private key.
+ */
+export const signingKeyPairFromJson = (json: string): SigningKeyPair => Recrypt.signingKeyPairFromJson(json);
+
+/**
+ * Serialize the provided transform key as versioned JSON with unpadded base64url fields.
+ */
+export const transformKeyToJson = (transformKey: TransformKey): string => Recrypt.transformKeyToJson(transformKey);
+
+/**
+ * Parse JSON produced by transformKeyToJson.
+ */
+export const transformKeyFromJson = (json: string): TransformKey => Recrypt.transformKeyFromJson(json);
+
+/**
+ * Serialize the provided encrypted value as versioned JSON with unpadded base64url fields.
+ */
+export const encryptedValueToJson = (encryptedValue: EncryptedValue): string => Recrypt.encryptedValueToJson(encryptedValue);
+
+/**
+ * Parse JSON produced by encryptedValueToJson.
+ */
+export const encryptedValueFromJson = (json: string): EncryptedValue => Recrypt.encryptedValueFromJson(json);
+
+/**
+ * Export the provided recrypt public key, recrypt key pair or Ed25519 signing key pair as a JWK. Ed25519 key pairs use the standard OKP form and
+ * recrypt keys use the private RECRYPT key type.
+ */
+export const exportJwk = (key: PublicKeyInput | KeyPair | SigningKeyPair): Jwk => Recrypt.exportJwk(key);
+
+/**
+ * Expand a 32 byte Ed25519 seed, the private key form most other libraries use, into a signing key pair with the 64 byte private key form returned by
+ * generateEd25519KeyPair. Private signing key parameters accept either form, so this is only needed to store the 64 byte form.
+ */
+export const expandEd25519Seed = (seed: Bytes): SigningKeyPair => Recrypt.expandEd25519Seed(seed);
+
+/**
+ * Extract the 32 byte Ed25519 seed from the provided private signing key. Throws if the public half of a 64 byte key doesn't match its seed.
+ */
+export const extractEd25519Seed = (privateSigningKey: Bytes): Uint8Array => Recrypt.extractEd25519Seed(privateSigningKey);
+
+/**
+ * Export the provided Ed25519 private signing key as a DER encoded PKCS#8 PrivateKeyInfo. The result can be loaded by WebCrypto
+ * (`importKey("pkcs8", ...)`), Node's `crypto.createPrivateKey` and OpenSSL.
+ */
+export const exportSigningKeyPkcs8 = (privateSigningKey: Bytes): Uint8Array => Recrypt.exportSigningKeyPkcs8(privateSigningKey);
+
+/**
+ * Same as exportSigningKeyPkcs8, but returns a PEM encoded "PRIVATE KEY" block.
+ */
+export const exportSigningKeyPkcs8Pem = (privateSigningKey: Bytes): string => Recrypt.exportSigningKeyPkcs8Pem(privateSigningKey);
+
+/**
+ * Import a DER encoded PKCS#8 Ed25519 private key, such as one exported by WebCrypto, Node or OpenSSL, into a signing key pair.
+ */
+export const importSigningKeyPkcs8 = (pkcs8: Bytes): SigningKeyPair => Recrypt.importSigningKeyPkcs8(pkcs8);
+
+/**
+ * Import a PEM encoded PKCS#8 Ed25519 private key into a signing key pair.
+ */
+export const importSigningKeyPkcs8Pem = (pem: string): SigningKeyPair => Recrypt.importSigningKeyPkcs8Pem(pem);
+
+/**
+ * Export the provided 32 byte Ed25519 public signing key as a DER encoded SubjectPublicKeyInfo.
+ */
+export const exportSigningPublicKeySpki = (publicSigningKey: Bytes): Uint8Array => Recrypt.exportSigningPublicKeySpki(publicSigningKey);
+
+/**
+ * Same as exportSigningPublicKeySpki, but returns a PEM encoded "PUBLIC KEY" block.
+ */
+export const exportSigningPublicKeySpkiPem = (publicSigningKey: Bytes): string => Recrypt.exportSigningPublicKeySpkiPem(publicSigningKey);
+
+/**
+ * Import a DER encoded SubjectPublicKeyInfo holding an Ed25519 public key. Returns the 32 byte public signing key.
+ */
+export const importSigningPublicKeySpki = (spki: Bytes): PublicSigningKey => Recrypt.importSigningPublicKeySpki(spki);
+
+/**
+ * Import a PEM encoded SubjectPublicKeyInfo holding an Ed25519 public key. Returns the 32 byte public signing key.
+ */
+export const importSigningPublicKeySpkiPem = (pem: string): PublicSigningKey => Recrypt.importSigningPublicKeySpkiPem(pem);
+
+/**
+ * Compress the provided public key into 33 bytes. Compressed public keys can be passed anywhere a public key is accepted.
+ */
+export const compressPublicKey = (publicKey: PublicKeyInput): Uint8Array => Recrypt.compressPublicKey(publicKey);
+
+/**
+ * Convert a 33 byte compressed public key back into a public key with x/y coordinates, validating that it is on the curve.
+ */
+export const decompressPublicKey = (compressedPublicKey: Bytes): PublicKey => Recrypt.decompressPublicKey(compressedPublicKey);
+
+/**
+ * Check that the provided public key is well-formed and on the curve. Returns one diagnostic per problem found, or an empty array if the key is valid.
+ */
+export const validatePublicKey = (publicKey: PublicKeyInput): ValidationDiagnostic[] => Recrypt.validatePublicKey(publicKey);
+
+/**
+ * Returns whether the provided public key is well-formed and on the curve.
+ */
+export const isValidPublicKey = (publicKey: PublicKeyInput): boolean => Recrypt.isValidPublicKey(publicKey);
+
+/**
+ * Check that the provided transform key is well-formed. Returns one diagnostic per problem found, or an empty array if the transform key is valid.
+ */
+export const validateTransformKey = (transformKey: TransformKey): ValidationDiagnostic[] => Recrypt.validateTransformKey(transformKey);
+
+/**
+ * Check that the provided encrypted value is well-formed without attempting to decrypt it. Returns one diagnostic per problem found, or an empty
+ * array if the encrypted value is valid.
+ */
+export const validateEncryptedValue = (encryptedValue: EncryptedValue): ValidationDiagnostic[] => Recrypt.validateEncryptedValue(encryptedValue);
 
 /**
  * Export the entire EncryptedSearch struct out directly. No need to shim this at any level.
  */
 export const EncryptedSearch = Recrypt.EncryptedSearch;
+
+/**
+ * Export the wasm value classes directly. Instances hold already validated values or private key handles and must be released with free().
+ */
+export {PrivateKeyHandle, SigningKeyHandle, WasmEncryptedValue, WasmPublicKey, WasmTransformKey} from "../target/recrypt_wasm_binding";
@@ -1,5 +1,14 @@
+/**
+ * Any of the byte forms accepted as input, including nested object fields. Node Buffers are Uint8Arrays and are also accepted.
+ * Outputs are always Uint8Arrays.
+ */
+export type Bytes = Uint8Array | ArrayBuffer | DataView | number[];
 export type PrivateKey = Uint8Array;
 export type PublicSigningKey = Uint8Array;
+/**
+ * An Ed25519 private signing key. generateEd25519KeyPair returns the 64 byte seed and public key form, but anywhere a private
+ * signing key is accepted also takes the 32 byte seed used by most other libraries.
+ */
 export type PrivateSigningKey = Uint8Array;
 export type Signature = Uint8Array;
 export type Plaintext = Uint8Array;
@@ -7,6 +16,10 @@ export interface PublicKey {
     x: Uint8Array;
     y: Uint8Array;
 }
+/**
+ * A public key object, or a 33 byte compressed public key produced by compressPublicKey.
+ */
+export type PublicKeyInput = PublicKey | Bytes;
 export interface KeyPair {
     publicKey: PublicKey;
     privateKey: PrivateKey;
@@ -29,6 +42,85 @@ export interface EncryptedValue {
     publicSigningKey: PublicSigningKey;
     signature: Uint8Array;
 }
+/**
+ * A JWK produced by exportJwk. Ed25519 signing key pairs use the standard OKP form with the 32 byte seed as d. Recrypt keys
+ * use the private RECRYPT key type with the public key coordinates as x and y, plus d for private keys. Byte members are
+ * unpadded base64url.
+ */
+export interface Jwk {
+    kty: "OKP" | "RECRYPT";
+    crv: "Ed25519" | "Fp256";
+    x: string;
+    y?: string;
+    d?: string;
+    [member: string]: unknown;
+}
+/**
+ * Contents of an identity bundle returned by importIdentity. createdAt is milliseconds since the Unix epoch, and keyId and
+ * signingKeyId are the RFC 7638 JWK thumbprints of the two public keys.
+ */
+export interface Identity {
+    keyPair: KeyPair;
+    signingKeyPair: SigningKeyPair;
+    createdAt: number;
+    keyId: string;
+    signingKeyId: string;
+}
+/**
+ * Argon2id costs for wrapPrivateKeyWithPassword. memoryCost is in KiB, from 8 times parallelism up to 1048576, and defaults to
+ * 19456. iterations is from 1 to 100 and defaults to 2. parallelism is from 1 to 16 and defaults to 1.
+ */
+export interface PasswordWrapOptions {
+    memoryCost?: number;
+    iterations?: number;
+    parallelism?: number;
+}
+/**
+ * Keys returned by unwrapPrivateKeyWithPassword. signingKeyPair is only present if a signing key was wrapped.
+ */
+export interface PasswordUnwrappedKeys {
+    keyPair: KeyPair;
+    signingKeyPair?: SigningKeyPair;
+}
+/**
+ * Keys returned by unwrapPrivateKeyHandleWithPassword, held in WASM memory. signingKey is only present if a signing key was
+ * wrapped.
+ */
+export interface PasswordUnwrappedKeyHandles {
+    keyPair: KeyPairHandle;
+    signingKey?: SigningKeyHandle;
+}
+/**
+ * Contents of a COSE_Encrypt envelope returned by decodeCoseEncrypt.
+ */
+export interface CoseEncrypt {
+    /**
+     * The recrypt encrypted value whose decrypted plaintext the AES-256-GCM content key is derived from.
+     */
+    encryptedDek: EncryptedValue;
+    /**
+     * AES-256-GCM ciphertext, including the tag.
+     */
+    ciphertext: Uint8Array;
+    iv: Uint8Array;
+}
+/**
+ * Description of an EncryptedValue returned by inspectEncryptedValue.
+ */
+export interface EncryptedValueInfo {
+    variant: "EncryptedOnceValue" | "TransformedValue";
+    /**
+     * Number of times the value has been transformed. 0 for an EncryptedOnceValue.
+     */
+    transformLevel: number;
+    /**
+     * The ephemeralPublicKey of the transform key used for each hop, in the order the transforms happened. The public key a
+     * value was transformed to isn't stored in the value itself, so match these against the ephemeralPublicKey of known
+     * TransformKeys to find who the value was delegated to (their toPublicKey).
+     */
+    transformedWith: PublicKey[];
+    publicSigningKey: PublicSigningKey;
+}
 export interface TransformKey {
     ephemeralPublicKey: PublicKey;
     toPublicKey: PublicKey;
@@ -37,32 +129,203 @@ export interface TransformKey {
     publicSigningKey: PublicSigningKey;
     signature: Uint8Array;
 }
+/**
+ * Stable codes set as the `code` property on every Error thrown by this library.
+ */
+export type RecryptErrorCode =
+    | "DecryptFailed"
+    | "InvalidEncryptedMessageSignature"
+    | "InvalidPublicKey"
+    | "InvalidTransformKey"
+    | "InvalidSigningKeyPair"
+    | "InputWrongSize"
+    | "DecodeFailure"
+    | "SerdeError"
+    | "InvalidSearchInput"
+    | "RandomFailure";
+export interface RecryptError extends Error {
+    code: RecryptErrorCode;
+    /**
+     * Path to the input value that caused the error, e.g. `encryptedValue.transformBlocks[1].publicKey.x`, when the error
+     * can be attributed to a single input.
+     */
+    field?: string;
+}
+/**
+ * A single problem found by validatePublicKey, validateTransformKey or validateEncryptedValue. The code, reason and field
+ * match the Error that would be thrown if the value was used.
+ */
+export interface ValidationDiagnostic {
+    field?: string;
+    code: RecryptErrorCode;
+    reason: string;
+    /**
+     * Set, along with actualSize, when the value was not the expected number of bytes.
+     */
+    expectedSize?: number;
+    actualSize?: number;
+}
+/**
+ * Already validated Recrypt values held in WASM memory. Instances must be released with free() once they are no longer needed.
+ */
+export class WasmPublicKey {
+    private constructor();
+    free(): void;
+    static fromObject(publicKey: PublicKeyInput): WasmPublicKey;
+    toObject(): PublicKey;
+    readonly x: Uint8Array;
+    readonly y: Uint8Array;
+}
+export class WasmTransformKey {
+    private constructor();
+    free(): void;
+    static fromObject(transformKey: TransformKey): WasmTransformKey;
+    toObject(): TransformKey;
+    readonly ephemeralPublicKey: WasmPublicKey;
+    readonly toPublicKey: WasmPublicKey;
+    readonly encryptedTempKey: Uint8Array;
+    readonly hashedTempKey: Uint8Array;
+    readonly publicSigningKey: PublicSigningKey;
+    readonly signature: Uint8Array;
+}
+export class WasmEncryptedValue {
+    private constructor();
+    free(): void;
+    static fromObject(encryptedValue: EncryptedValue): WasmEncryptedValue;
+    toObject(): EncryptedValue;
+    readonly ephemeralPublicKey: WasmPublicKey;
+    readonly encryptedMessage: Uint8Array;
+    readonly authHash: Uint8Array;
+    readonly transformBlocks: TransformBlock[];
+    readonly publicSigningKey: PublicSigningKey;
+    readonly signature: Uint8Array;
+    inspect(): EncryptedValueInfo;
+}
+/**
+ * A Recrypt private key held in WASM memory. The key bytes are never returned to JS and can only be exported with
+ * wrapPrivateKey or wrapPrivateKeyWithPassword. free() zeroes the key.
+ */
+export class PrivateKeyHandle {
+    private constructor();
+    free(): void;
+    readonly publicKey: PublicKey;
+}
+/**
+ * An Ed25519 signing key pair held in WASM memory, parsed once when the handle is created. Only the public key can be read
+ * back out. free() zeroes the key pair.
+ */
+export class SigningKeyHandle {
+    private constructor();
+    free(): void;
+    readonly publicKey: PublicSigningKey;
+}
+export interface KeyPairHandle {
+    privateKey: PrivateKeyHandle;
+    publicKey: PublicKey;
+}
 export class Api256 {
     constructor();
     generateKeyPair(): KeyPair;
     generateEd25519KeyPair(): SigningKeyPair;
-    ed25519Sign(signingPrivateKey: Uint8Array, message: Uint8Array): Signature;
-    ed25519Verify(signingPublicKey: Uint8Array, message: Uint8Array, signature: Uint8Array): boolean;
-    computeEd25519PublicKey(signingPrivateKey: Uint8Array): PublicSigningKey;
+    deriveKeyPair(masterSeed: Bytes, path: string): KeyPair;
+    deriveSigningKeyPair(masterSeed: Bytes, path: string): SigningKeyPair;
+    ed25519Sign(signingPrivateKey: Bytes | SigningKeyHandle, message: Bytes): Signature;
+    ed25519Verify(signingPublicKey: Bytes, message: Bytes, signature: Bytes): boolean;
+    computeEd25519PublicKey(signingPrivateKey: Bytes | SigningKeyHandle): PublicSigningKey;
     generatePlaintext(): Plaintext;
-    generateTransformKey(fromPrivateKey: PrivateKey, toPublicKey: PublicKey, privateSigningKey: PrivateSigningKey): TransformKey;
-    computePublicKey(privateKey: PrivateKey): PublicKey;
-    hash256(hashableBytes: Uint8Array): Uint8Array;
-    deriveSymmetricKey(plaintext: Plaintext): Uint8Array;
-    encrypt(plaintext: Plaintext, toPublicKey: PublicKey, privateSigningKey: PrivateSigningKey): EncryptedValue;
-    transform(encryptedValue: EncryptedValue, transformKey: TransformKey, privateSigningKey: PrivateSigningKey): EncryptedValue;
-    decrypt(encryptedValue: EncryptedValue, privateKey: PrivateKey): Plaintext;
-    schnorrSign(privateKey: Uint8Array, publicKey: PublicKey, message: Uint8Array): Signature;
-    schnorrVerify(publicKey: PublicKey, augmentedPrivateKey: Uint8Array | undefined, message: Uint8Array, signature: Signature): boolean;
+    generateTransformKey(fromPrivateKey: Bytes | PrivateKeyHandle, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | SigningKeyHandle): TransformKey;
+    generateTransformKey(fromPrivateKey: Bytes | PrivateKeyHandle, toPublicKey: WasmPublicKey, privateSigningKey: Bytes | SigningKeyHandle): WasmTransformKey;
+    computePublicKey(privateKey: Bytes | PrivateKeyHandle): PublicKey;
+    hash256(hashableBytes: Bytes): Uint8Array;
+    deriveSymmetricKey(plaintext: Bytes): Uint8Array;
+    encrypt(plaintext: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | SigningKeyHandle): EncryptedValue;
+    encrypt(plaintext: Bytes, toPublicKey: WasmPublicKey, privateSigningKey: Bytes | SigningKeyHandle): WasmEncryptedValue;
+    transform(encryptedValue: EncryptedValue, transformKey: TransformKey, privateSigningKey: Bytes | SigningKeyHandle): EncryptedValue;
+    transform(
+        encryptedValue: EncryptedValue | WasmEncryptedValue,
+        transformKey: TransformKey | WasmTransformKey,
+        privateSigningKey: Bytes | SigningKeyHandle
+    ): WasmEncryptedValue;
+    decrypt(encryptedValue: EncryptedValue | WasmEncryptedValue, privateKey: Bytes | PrivateKeyHandle): Plaintext;
+    schnorrSign(privateKey: Bytes | PrivateKeyHandle, publicKey: PublicKeyInput, message: Bytes): Signature;
+    schnorrVerify(publicKey: PublicKeyInput, augmentedPrivateKey: Bytes | undefined, message: Bytes, signature: Bytes): boolean;
+    encryptJwe(message: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | SigningKeyHandle): string;
+    encryptJweJson(message: Bytes, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | SigningKeyHandle): string;
+    transformJwe(jwe: string, transformKey: TransformKey, privateSigningKey: Bytes | SigningKeyHandle): string;
+    decryptJwe(jwe: string, privateKey: Bytes | PrivateKeyHandle): Uint8Array;
+    exportIdentity(keyPair: KeyPair, signingKeyPair: SigningKeyPair, createdAt?: number): string;
+    importIdentity(bundle: string): Identity;
+    importJwk(jwk: Jwk): PublicKey | KeyPair | SigningKeyPair;
+    wrapPrivateKeyWithPassword(
+        privateKey: Bytes | PrivateKeyHandle,
+        password: string | Bytes,
+        privateSigningKey?: Bytes | SigningKeyHandle,
+        options?: PasswordWrapOptions
+    ): Uint8Array;
+    unwrapPrivateKeyWithPassword(wrappedKey: Bytes, password: string | Bytes): PasswordUnwrappedKeys;
+    unwrapPrivateKeyHandleWithPassword(wrappedKey: Bytes, password: string | Bytes): PasswordUnwrappedKeyHandles;
+    generateKeyPairHandle(): KeyPairHandle;
+    importPrivateKey(privateKey: Bytes): PrivateKeyHandle;
+    wrapPrivateKey(privateKey: PrivateKeyHandle, toPublicKey: PublicKeyInput, privateSigningKey: Bytes | SigningKeyHandle): string;
+    unwrapPrivateKey(wrappedKey: string, unwrappingKey: Bytes | PrivateKeyHandle): PrivateKeyHandle;
+    generateSigningKeyHandle(): SigningKeyHandle;
+    importSigningKey(privateSigningKey: Bytes): SigningKeyHandle;
 }
 export class EncryptedSearch {
     constructor();
-    generateHashesForString(s: string, salt: Uint8Array, partitionId?: string): Uint32Array;
-    generateHashesForStringWithPadding(s: string, salt: Uint8Array, partitionId?: string): Uint32Array;
+    generateHashesForString(s: string, salt: Bytes, partitionId?: string): Uint32Array;
+    generateHashesForStringWithPadding(s: string, salt: Bytes, partitionId?: string): Uint32Array;
     static transliterateString(s: string): string;
 }
 export function transformKeyToBytes256(transformKey: TransformKey): Uint8Array;
-export function augmentTransformKey256(transformKey: TransformKey, privateKey: PrivateKey): TransformKey;
-export function augmentPublicKey256(currentPublicKey: PublicKey, otherPublicKey: PublicKey): PublicKey;
-export function addPrivateKeys(privateKeyA: Uint8Array, privateKeyB: Uint8Array): Uint8Array;
-export function subtractPrivateKeys(privateKeyA: Uint8Array, privateKeyB: Uint8Array): Uint8Array;
+export function augmentTransformKey256(transformKey: TransformKey, privateKey: Bytes): TransformKey;
+export function augmentPublicKey256(currentPublicKey: PublicKeyInput, otherPublicKey: PublicKeyInput): PublicKey;
+export function addPrivateKeys(privateKeyA: Bytes, privateKeyB: Bytes): Uint8Array;
+export function subtractPrivateKeys(privateKeyA: Bytes, privateKeyB: Bytes): Uint8Array;
+export function inspectEncryptedValue(encryptedValue: EncryptedValue): EncryptedValueInfo;
+export function encodeEncryptedValue(encryptedValue: EncryptedValue): Uint8Array;
+export function decodeEncryptedValue(encodedValue: Bytes): EncryptedValue;
+export function encodeTransformKey(transformKey: TransformKey): Uint8Array;
+export function decodeTransformKey(encodedKey: Bytes): TransformKey;
+export function encodeEncryptedValueProtobuf(encryptedValue: EncryptedValue): Uint8Array;
+export function decodeEncryptedValueProtobuf(encodedValue: Bytes): EncryptedValue;
+export function encodeTransformKeyProtobuf(transformKey: TransformKey): Uint8Array;
+export function decodeTransformKeyProtobuf(encodedKey: Bytes): TransformKey;
+export function encodePublicKeyCbor(publicKey: PublicKeyInput): Uint8Array;
+export function decodePublicKeyCbor(encodedKey: Bytes): PublicKey;
+export function encodeEncryptedValueCbor(encryptedValue: EncryptedValue): Uint8Array;
+export function decodeEncryptedValueCbor(encodedValue: Bytes): EncryptedValue;
+export function encodeTransformKeyCbor(transformKey: TransformKey): Uint8Array;
+export function decodeTransformKeyCbor(encodedKey: Bytes): TransformKey;
+export function encodeSigningKeyPairCbor(signingKeyPair: SigningKeyPair): Uint8Array;
+export function decodeSigningKeyPairCbor(encodedKeyPair: Bytes): SigningKeyPair;
+export function coseEncryptAad(): Uint8Array;
+export function encodeCoseEncrypt(encryptedDek: EncryptedValue, ciphertext: Bytes, iv: Bytes): Uint8Array;
+export function decodeCoseEncrypt(envelope: Bytes): CoseEncrypt;
+export function publicKeyToJson(publicKey: PublicKeyInput): string;
+export function publicKeyFromJson(json: string): PublicKey;
+export function keyPairToJson(keyPair: KeyPair): string;
+export function keyPairFromJson(json: string): KeyPair;
+export function signingKeyPairToJson(signingKeyPair: SigningKeyPair): string;
+export function signingKeyPairFromJson(json: string): SigningKeyPair;
+export function transformKeyToJson(transformKey: TransformKey): string;
+export function transformKeyFromJson(json: string): TransformKey;
+export function encryptedValueToJson(encryptedValue: EncryptedValue): string;
+export function encryptedValueFromJson(json: string): EncryptedValue;
+export function exportJwk(key: PublicKeyInput | KeyPair | SigningKeyPair): Jwk;
+export function expandEd25519Seed(seed: Bytes): SigningKeyPair;
+export function extractEd25519Seed(privateSigningKey: Bytes): Uint8Array;
+export function exportSigningKeyPkcs8(privateSigningKey: Bytes): Uint8Array;
+export function exportSigningKeyPkcs8Pem(privateSigningKey: Bytes): string;
+export function importSigningKeyPkcs8(pkcs8: Bytes): SigningKeyPair;
+export function importSigningKeyPkcs8Pem(pem: string): SigningKeyPair;
+export function exportSigningPublicKeySpki(publicSigningKey: Bytes): Uint8Array;
+export function exportSigningPublicKeySpkiPem(publicSigningKey: Bytes): string;
+export function importSigningPublicKeySpki(spki: Bytes): PublicSigningKey;
+export function importSigningPublicKeySpkiPem(pem: string): PublicSigningKey;
+export function compressPublicKey(publicKey: PublicKeyInput): Uint8Array;
+export function decompressPublicKey(compressedPublicKey: Bytes): PublicKey;
+export function validatePublicKey(publicKey: PublicKeyInput): ValidationDiagnostic[];
+export function isValidPublicKey(publicKey: PublicKeyInput): boolean;
+export function validateTransformKey(transformKey: TransformKey): ValidationDiagnostic[];
+export function validateEncryptedValue(encryptedValue: EncryptedValue): ValidationDiagnostic[];
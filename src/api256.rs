@@ -1,24 +1,47 @@
 #![allow(non_snake_case)]
 
-use crate::util::{self, JsError, WasmError};
-use gloo_utils::format::JsValueSerdeExt;
+use crate::cbor;
+use crate::derivation;
+use crate::diagnostics;
+use crate::encoding;
+use crate::handles::{PrivateKeyHandle, SigningKeyHandle};
+use crate::identity::Identity;
+use crate::json;
+use crate::jwe;
+use crate::jwk;
+use crate::password::{self, Argon2Costs, PasswordWrappedKeys};
+use crate::pkcs8;
+use crate::protobuf;
+use crate::secrets;
+use crate::util::{self, BindingError, JsError, WasmError};
+use crate::values::{WasmEncryptedValue, WasmPublicKey, WasmTransformKey};
 use ironcore_search_helpers::{
     generate_hashes_for_string, generate_hashes_for_string_with_padding, transliterate_string,
 };
+use js_sys::{Array, JsString, Uint8Array};
+use rand::{rand_core::UnwrapErr, rngs::SysRng};
 use recrypt::{
     api::{
-        DefaultRng, Ed25519, Ed25519Signature, Hashable, Plaintext, PrivateKey, PublicSigningKey,
-        RandomBytes, Recrypt, ReseedingRng, SchnorrSignature, Sha256, Sha256Hashing,
-        SigningKeypair,
+        DefaultRng, Ed25519, Hashable, Plaintext, PrivateKey, PublicKey, RandomBytes, Recrypt,
+        ReseedingRng, SchnorrSignature, Sha256, SigningKeypair, TransformKey,
     },
     prelude::*,
 };
+use sha2::Digest;
 use std::sync::Mutex;
 use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+/**
+ * Recrypt's default RNG buffers a block of ChaCha output on the heap, so the bytes of recently generated private keys stay
+ * in WASM memory until the buffer is refilled. Reading straight from crypto.getRandomValues leaves nothing behind. SysRng
+ * only fails if crypto.getRandomValues is unavailable, in which case recrypt's default RNG would fail to seed as well.
+ */
+type UnbufferedRng = UnwrapErr<SysRng>;
 
 #[wasm_bindgen]
 pub struct Api256 {
-    api: Recrypt<Sha256, Ed25519, RandomBytes<DefaultRng>>,
+    api: Recrypt<Sha256, Ed25519, RandomBytes<UnbufferedRng>>,
 }
 
 #[wasm_bindgen]
@@ -26,7 +49,7 @@ impl Api256 {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Api256 {
         Api256 {
-            api: Recrypt::new(),
+            api: Recrypt::new_with_rand(UnwrapErr(SysRng)),
         }
     }
 
@@ -35,21 +58,76 @@ impl Api256 {
      * as Uint8Arrays.
      */
     pub fn generateKeyPair(&mut self) -> Result<JsValue, JsError> {
-        let (priv_key, pub_key) = self.api.generate_key_pair().map_err(WasmError::new)?;
-        Ok(
-            JsValue::from_serde(&util::key_pair_to_js_object(priv_key, pub_key))
-                .map_err(WasmError::new)?,
-        )
+        diagnostics::with_failure_context("generateKeyPair", &[], || {
+            secrets::with_cleared_stack(|| {
+                let (priv_key, pub_key) = self.api.generate_key_pair().map_err(WasmError::new)?;
+                Ok(util::key_pair_to_js_object(priv_key, pub_key).into())
+            })
+        })
     }
 
     /**
      * Generate and return an ed25519 signing public and private key.
      */
     pub fn generateEd25519KeyPair(&mut self) -> Result<JsValue, JsError> {
-        let signing_key_pair = self.api.generate_ed25519_key_pair();
-        Ok(
-            JsValue::from_serde(&util::signing_keys_to_js_object(signing_key_pair))
-                .map_err(WasmError::new)?,
+        diagnostics::with_failure_context("generateEd25519KeyPair", &[], || {
+            secrets::with_cleared_stack(|| {
+                let signing_key_pair = self.api.generate_ed25519_key_pair();
+                Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+            })
+        })
+    }
+
+    /**
+     * Derive a Recrypt key pair from the master seed at the provided path, such as "tenant/acme/device/laptop". The same seed
+     * and path always give the same key pair. See src/derivation.rs for the derivation.
+     */
+    pub fn deriveKeyPair(&self, master_seed: &JsValue, path: &JsValue) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context(
+            "deriveKeyPair",
+            &[("masterSeed", master_seed), ("path", path)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let key = derivation::derive_key(
+                        &Zeroizing::new(util::js_value_to_bytes(master_seed, "masterSeed")?),
+                        derivation::RECRYPT_PRIVATE_KEY_LABEL,
+                        &util::js_value_to_string(path, "path")?,
+                        "masterSeed",
+                        "path",
+                    )?;
+                    let private_key = PrivateKey::new(*key);
+                    let public_key = self.compute_public_key(&private_key)?;
+                    Ok(util::key_pair_to_js_object(private_key, public_key).into())
+                })
+            },
+        )
+    }
+
+    /**
+     * Derive an Ed25519 signing key pair from the master seed at the provided path. The same path gives an unrelated key
+     * to deriveKeyPair. See src/derivation.rs for the derivation.
+     */
+    pub fn deriveSigningKeyPair(
+        &self,
+        master_seed: &JsValue,
+        path: &JsValue,
+    ) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context(
+            "deriveSigningKeyPair",
+            &[("masterSeed", master_seed), ("path", path)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let seed = derivation::derive_key(
+                        &Zeroizing::new(util::js_value_to_bytes(master_seed, "masterSeed")?),
+                        derivation::ED25519_SEED_LABEL,
+                        &util::js_value_to_string(path, "path")?,
+                        "masterSeed",
+                        "path",
+                    )?;
+                    let signing_key_pair = util::seed_to_signing_keypair(&seed, "masterSeed")?;
+                    Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+                })
+            },
         )
     }
 
@@ -58,15 +136,24 @@ impl Api256 {
      */
     pub fn ed25519Sign(
         &mut self,
-        signing_private_key: &[u8],
-        message: &[u8],
+        signing_private_key: &JsValue,
+        message: &JsValue,
     ) -> Result<Vec<u8>, JsError> {
-        let signing_key_pair = SigningKeypair::from_bytes(&util::slice_to_fixed_64_bytes(
-            signing_private_key,
-            "privateSigningKey",
-        ))
-        .map_err(WasmError::new)?;
-        Ok(signing_key_pair.sign(&message.to_vec()).bytes().to_vec())
+        diagnostics::with_failure_context(
+            "ed25519Sign",
+            &[
+                ("signingPrivateKey", signing_private_key),
+                ("message", message),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let signing_key_pair =
+                        util::js_to_signing_keypair(signing_private_key, "privateSigningKey")?;
+                    let message = util::js_value_to_bytes(message, "message")?;
+                    Ok(util::ed25519_sign(&signing_key_pair, &message).to_vec())
+                })
+            },
+        )
     }
 
     /**
@@ -74,17 +161,28 @@ impl Api256 {
      */
     pub fn ed25519Verify(
         &mut self,
-        signing_public_key: &[u8],
-        message: &[u8],
-        signature: &[u8],
-    ) -> bool {
-        let public_signing_key = PublicSigningKey::new(util::slice_to_fixed_32_bytes(
-            signing_public_key,
-            "publicSigningKey",
-        ));
-        public_signing_key.verify(
-            &message.to_vec(),
-            &Ed25519Signature::new(util::slice_to_fixed_64_bytes(signature, "signature")),
+        signing_public_key: &JsValue,
+        message: &JsValue,
+        signature: &JsValue,
+    ) -> Result<bool, JsError> {
+        diagnostics::with_failure_context(
+            "ed25519Verify",
+            &[
+                ("signingPublicKey", signing_public_key),
+                ("message", message),
+                ("signature", signature),
+            ],
+            || {
+                let public_signing_key =
+                    util::js_to_fixed_32_bytes(signing_public_key, "publicSigningKey")?;
+                let signature = util::js_to_fixed_64_bytes(signature, "signature")?;
+                let message = util::js_value_to_bytes(message, "message")?;
+                Ok(util::ed25519_verify(
+                    &public_signing_key,
+                    &message,
+                    &signature,
+                ))
+            },
         )
     }
 
@@ -93,21 +191,26 @@ impl Api256 {
      */
     pub fn computeEd25519PublicKey(
         &mut self,
-        signing_private_key: &[u8],
+        signing_private_key: &JsValue,
     ) -> Result<Vec<u8>, JsError> {
-        let signing_key_pair = SigningKeypair::from_bytes(&util::slice_to_fixed_64_bytes(
-            signing_private_key,
-            "privateSigningKey",
-        ))
-        .map_err(WasmError::new)?;
-        Ok(signing_key_pair.public_key().bytes().to_vec())
+        diagnostics::with_failure_context(
+            "computeEd25519PublicKey",
+            &[("signingPrivateKey", signing_private_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let signing_key_pair =
+                        util::js_to_signing_keypair(signing_private_key, "privateSigningKey")?;
+                    Ok(signing_key_pair.public_key().bytes().to_vec())
+                })
+            },
+        )
     }
 
     /**
      * Generate a new Recrypt plaintext and return it as a Uint8Array.
      */
-    pub fn generatePlaintext(&mut self) -> Vec<u8> {
-        self.api.gen_plaintext().bytes().to_vec()
+    pub fn generatePlaintext(&mut self) -> Uint8Array {
+        secrets::with_cleared_stack(|| util::secret_bytes_to_js(self.api.gen_plaintext().bytes()))
     }
 
     /**
@@ -116,70 +219,88 @@ impl Api256 {
      */
     pub fn generateTransformKey(
         &mut self,
-        from_private_key: &[u8],
+        from_private_key: &JsValue,
         to_public_key: &JsValue,
-        private_signing_key: &[u8],
+        private_signing_key: &JsValue,
     ) -> Result<JsValue, JsError> {
-        let to_public_key_obj: util::JsPublicKey =
-            to_public_key.into_serde().map_err(WasmError::new)?;
-
-        let transform_key = self
-            .api
-            .generate_transform_key(
-                &PrivateKey::new(util::slice_to_fixed_32_bytes(
-                    from_private_key,
-                    "privateKey",
-                )),
-                &util::js_object_to_public_key(&to_public_key_obj)?,
-                &SigningKeypair::from_bytes(&util::slice_to_fixed_64_bytes(
-                    private_signing_key,
-                    "privateSigningKey",
-                ))
-                .map_err(WasmError::new)?,
-            )
-            .map_err(WasmError::new)?;
+        diagnostics::with_failure_context(
+            "generateTransformKey",
+            &[
+                ("fromPrivateKey", from_private_key),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transform_key = self
+                        .api
+                        .generate_transform_key(
+                            &PrivateKey::new(util::js_to_fixed_32_bytes(
+                                from_private_key,
+                                "privateKey",
+                            )?),
+                            &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
 
-        Ok(
-            JsValue::from_serde(&util::transform_key_to_js_object(transform_key))
-                .map_err(WasmError::new)?,
+                    Ok(util::transform_key_to_js_object(transform_key).into())
+                })
+            },
         )
     }
 
     /**
      * Compute the private key for the provided public key. Returns a public key object in x/y form.
      */
-    pub fn computePublicKey(&mut self, private_key: &[u8]) -> Result<JsValue, JsError> {
-        let computed_public_key = self
-            .api
-            .compute_public_key(&PrivateKey::new(util::slice_to_fixed_32_bytes(
-                private_key,
-                "privateKey",
-            )))
-            .map_err(WasmError::new)?;
-        Ok(
-            JsValue::from_serde(&util::public_key_to_js_object(computed_public_key))
-                .map_err(WasmError::new)?,
+    pub fn computePublicKey(&mut self, private_key: &JsValue) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context(
+            "computePublicKey",
+            &[("privateKey", private_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let computed_public_key = self
+                        .api
+                        .compute_public_key(&PrivateKey::new(util::js_to_fixed_32_bytes(
+                            private_key,
+                            "privateKey",
+                        )?))
+                        .map_err(WasmError::new)?;
+                    Ok(util::public_key_to_js_object(computed_public_key).into())
+                })
+            },
         )
     }
 
     /**
-     * Hash the incoming bytes to _exactly_ 32 bytes. Useful for generating PrivateKeys, among other things.
+     * Hash the incoming bytes to _exactly_ 32 bytes. Useful for generating PrivateKeys, among other things. The bytes are
+     * streamed out of JS memory in chunks so large inputs are never copied into WASM memory in full.
      */
-    pub fn hash256(&mut self, hashable_bytes: &[u8]) -> Vec<u8> {
-        Sha256.hash(&hashable_bytes.to_vec()).to_vec()
+    pub fn hash256(&mut self, hashable_bytes: &JsValue) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context("hash256", &[("hashableBytes", hashable_bytes)], || {
+            let mut hasher = sha2::Sha256::new();
+            util::for_each_js_bytes_chunk(hashable_bytes, "hashableBytes", |chunk| {
+                hasher.update(chunk)
+            })?;
+            Ok(hasher.finalize().to_vec())
+        })
     }
 
     /**
      * Derives a symmetric key from the provided plaintext.
      */
-    pub fn deriveSymmetricKey(&mut self, plaintext: &[u8]) -> Vec<u8> {
-        let symmetric_key =
-            self.api
-                .derive_symmetric_key(&Plaintext::new(util::slice_to_fixed_384_bytes(
-                    plaintext,
-                    "plaintext",
-                )));
-        symmetric_key.bytes().to_vec()
+    pub fn deriveSymmetricKey(&mut self, plaintext: &JsValue) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context("deriveSymmetricKey", &[("plaintext", plaintext)], || {
+            secrets::with_cleared_stack(|| {
+                let symmetric_key =
+                    self.api
+                        .derive_symmetric_key(&Plaintext::new(util::js_to_fixed_384_bytes(
+                            plaintext,
+                            "plaintext",
+                        )?));
+                Ok(util::secret_bytes_to_js(symmetric_key.bytes()))
+            })
+        })
     }
 
     /**
@@ -188,29 +309,31 @@ impl Api256 {
      */
     pub fn encrypt(
         &mut self,
-        plaintext: &[u8],
+        plaintext: &JsValue,
         to_public_key: &JsValue,
-        private_signing_key: &[u8],
+        private_signing_key: &JsValue,
     ) -> Result<JsValue, JsError> {
-        let to_public_key_obj: util::JsPublicKey =
-            to_public_key.into_serde().map_err(WasmError::new)?;
-
-        let encrypted_value = self
-            .api
-            .encrypt(
-                &Plaintext::new(util::slice_to_fixed_384_bytes(plaintext, "plaintext")),
-                &util::js_object_to_public_key(&to_public_key_obj)?,
-                &SigningKeypair::from_bytes(&util::slice_to_fixed_64_bytes(
-                    private_signing_key,
-                    "privateSigningKey",
-                ))
-                .map_err(WasmError::new)?,
-            )
-            .map_err(WasmError::new)?;
+        diagnostics::with_failure_context(
+            "encrypt",
+            &[
+                ("plaintext", plaintext),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let encrypted_value = self
+                        .api
+                        .encrypt(
+                            &Plaintext::new(util::js_to_fixed_384_bytes(plaintext, "plaintext")?),
+                            &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
 
-        Ok(
-            JsValue::from_serde(&util::encrypted_value_to_js_object(encrypted_value))
-                .map_err(WasmError::new)?,
+                    Ok(util::encrypted_value_to_js_object(encrypted_value).into())
+                })
+            },
         )
     }
 
@@ -222,30 +345,30 @@ impl Api256 {
         &mut self,
         encrypted_value: &JsValue,
         transform_key: &JsValue,
-        private_signing_key: &[u8],
+        private_signing_key: &JsValue,
     ) -> Result<JsValue, JsError> {
-        let encrypted_value_js: util::JsEncryptedValue =
-            encrypted_value.into_serde().map_err(WasmError::new)?;
-        let transform_key_js: util::JsTransformKey =
-            transform_key.into_serde().map_err(WasmError::new)?;
-
-        let transformed_encrypted_value = self
-            .api
-            .transform(
-                util::js_object_to_encrypted_value(encrypted_value_js)?,
-                util::js_object_to_transform_key(transform_key_js)?,
-                &SigningKeypair::from_bytes(&util::slice_to_fixed_64_bytes(
-                    private_signing_key,
-                    "privateSigningKey",
-                ))
-                .map_err(WasmError::new)?,
-            )
-            .map_err(WasmError::new)?;
+        diagnostics::with_failure_context(
+            "transform",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("transformKey", transform_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transformed_encrypted_value = self
+                        .api
+                        .transform(
+                            util::js_object_to_encrypted_value(encrypted_value, "encryptedValue")?,
+                            util::js_object_to_transform_key(transform_key, "transformKey")?,
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
 
-        Ok(JsValue::from_serde(&util::encrypted_value_to_js_object(
-            transformed_encrypted_value,
-        ))
-        .map_err(WasmError::new)?)
+                    Ok(util::encrypted_value_to_js_object(transformed_encrypted_value).into())
+                })
+            },
+        )
     }
 
     /**
@@ -254,20 +377,167 @@ impl Api256 {
     pub fn decrypt(
         &self,
         encrypted_value: &JsValue,
-        private_key: &[u8],
-    ) -> Result<Vec<u8>, JsError> {
-        let encrypted_value_js: util::JsEncryptedValue =
-            encrypted_value.into_serde().map_err(WasmError::new)?;
+        private_key: &JsValue,
+    ) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context(
+            "decrypt",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("privateKey", private_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let decrypted_value = self
+                        .api
+                        .decrypt(
+                            util::js_object_to_encrypted_value(encrypted_value, "encryptedValue")?,
+                            &PrivateKey::new(util::js_to_fixed_32_bytes(
+                                private_key,
+                                "privateKey",
+                            )?),
+                        )
+                        .map_err(WasmError::new)?;
 
-        let decrypted_value = self
-            .api
-            .decrypt(
-                util::js_object_to_encrypted_value(encrypted_value_js)?,
-                &PrivateKey::new(util::slice_to_fixed_32_bytes(private_key, "privateKey")),
-            )
-            .map_err(WasmError::new)?;
+                    Ok(util::secret_bytes_to_js(decrypted_value.bytes()))
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as generateTransformKey, but takes an already validated WasmPublicKey and returns a WasmTransformKey.
+     */
+    pub fn generateTransformKeyWasm(
+        &mut self,
+        from_private_key: &JsValue,
+        to_public_key: &WasmPublicKey,
+        private_signing_key: &JsValue,
+    ) -> Result<WasmTransformKey, JsError> {
+        diagnostics::with_failure_context(
+            "generateTransformKeyWasm",
+            &[
+                ("fromPrivateKey", from_private_key),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transform_key = self
+                        .api
+                        .generate_transform_key(
+                            &PrivateKey::new(util::js_to_fixed_32_bytes(
+                                from_private_key,
+                                "privateKey",
+                            )?),
+                            &to_public_key.key,
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmTransformKey { key: transform_key })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as encrypt, but takes an already validated WasmPublicKey and returns a WasmEncryptedValue.
+     */
+    pub fn encryptWasm(
+        &mut self,
+        plaintext: &JsValue,
+        to_public_key: &WasmPublicKey,
+        private_signing_key: &JsValue,
+    ) -> Result<WasmEncryptedValue, JsError> {
+        diagnostics::with_failure_context(
+            "encryptWasm",
+            &[
+                ("plaintext", plaintext),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let encrypted_value = self
+                        .api
+                        .encrypt(
+                            &Plaintext::new(util::js_to_fixed_384_bytes(plaintext, "plaintext")?),
+                            &to_public_key.key,
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmEncryptedValue {
+                        value: encrypted_value,
+                    })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as transform, but takes an already validated WasmEncryptedValue and WasmTransformKey and returns a
+     * WasmEncryptedValue. Neither argument is consumed.
+     */
+    pub fn transformWasm(
+        &mut self,
+        encrypted_value: &WasmEncryptedValue,
+        transform_key: &WasmTransformKey,
+        private_signing_key: &JsValue,
+    ) -> Result<WasmEncryptedValue, JsError> {
+        diagnostics::with_failure_context(
+            "transformWasm",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("transformKey", transform_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transformed_encrypted_value = self
+                        .api
+                        .transform(
+                            encrypted_value.value.clone(),
+                            transform_key.key.clone(),
+                            &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmEncryptedValue {
+                        value: transformed_encrypted_value,
+                    })
+                })
+            },
+        )
+    }
 
-        Ok(decrypted_value.bytes().to_vec())
+    /**
+     * Same as decrypt, but takes an already validated WasmEncryptedValue.
+     */
+    pub fn decryptWasm(
+        &self,
+        encrypted_value: &WasmEncryptedValue,
+        private_key: &JsValue,
+    ) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context(
+            "decryptWasm",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("privateKey", private_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let decrypted_value = self
+                        .api
+                        .decrypt(
+                            encrypted_value.value.clone(),
+                            &PrivateKey::new(util::js_to_fixed_32_bytes(
+                                private_key,
+                                "privateKey",
+                            )?),
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(util::secret_bytes_to_js(decrypted_value.bytes()))
+                })
+            },
+        )
     }
 
     /**
@@ -275,18 +545,29 @@ impl Api256 {
      */
     pub fn schnorrSign(
         &mut self,
-        private_key: &[u8],
+        private_key: &JsValue,
         public_key_obj: &JsValue,
-        message: &[u8],
+        message: &JsValue,
     ) -> Result<Vec<u8>, JsError> {
-        let public_key: util::JsPublicKey = public_key_obj.into_serde().map_err(WasmError::new)?;
-
-        let signature = self.api.schnorr_sign(
-            &PrivateKey::new(util::slice_to_fixed_32_bytes(private_key, "privateKey")),
-            &util::js_object_to_public_key(&public_key)?,
-            &message.to_vec(),
-        );
-        Ok(signature.bytes().to_vec())
+        diagnostics::with_failure_context(
+            "schnorrSign",
+            &[
+                ("privateKey", private_key),
+                ("publicKeyObj", public_key_obj),
+                ("message", message),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let message = util::js_value_to_bytes(message, "message")?;
+                    let signature = self.api.schnorr_sign(
+                        &PrivateKey::new(util::js_to_fixed_32_bytes(private_key, "privateKey")?),
+                        &util::js_object_to_public_key(public_key_obj, "publicKey")?,
+                        &util::HashableSlice(&message),
+                    );
+                    Ok(signature.bytes().to_vec())
+                })
+            },
+        )
     }
 
     /**
@@ -296,149 +577,1641 @@ impl Api256 {
     pub fn schnorrVerify(
         &mut self,
         public_key_obj: &JsValue,
-        augmented_private_key: Option<Vec<u8>>,
-        message: &[u8],
-        signature: &[u8],
+        augmented_private_key: &JsValue,
+        message: &JsValue,
+        signature: &JsValue,
     ) -> Result<bool, JsError> {
-        let public_key: util::JsPublicKey = public_key_obj.into_serde().map_err(WasmError::new)?;
-
-        Ok(self.api.schnorr_verify(
-            &util::js_object_to_public_key(&public_key)?,
-            augmented_private_key
-                .map(|v| PrivateKey::new(util::vector_to_fixed_32_bytes(&v, "augmentedPrivateKey")))
-                .as_ref(),
-            &message.to_vec(),
-            SchnorrSignature::new(util::slice_to_fixed_64_bytes(signature, "signature")),
-        ))
+        diagnostics::with_failure_context(
+            "schnorrVerify",
+            &[
+                ("publicKeyObj", public_key_obj),
+                ("augmentedPrivateKey", augmented_private_key),
+                ("message", message),
+                ("signature", signature),
+            ],
+            || {
+                let augmented_private_key =
+                    util::js_value_to_optional_bytes(augmented_private_key, "augmentedPrivateKey")?
+                        .map(|v| {
+                            util::slice_to_fixed_32_bytes(&v, "augmentedPrivateKey")
+                                .map(PrivateKey::new)
+                        })
+                        .transpose()?;
+
+                let message = util::js_value_to_bytes(message, "message")?;
+                Ok(self.api.schnorr_verify(
+                    &util::js_object_to_public_key(public_key_obj, "publicKey")?,
+                    augmented_private_key.as_ref(),
+                    &util::HashableSlice(&message),
+                    SchnorrSignature::new(util::js_to_fixed_64_bytes(signature, "signature")?),
+                ))
+            },
+        )
     }
-}
 
-#[wasm_bindgen]
-pub struct EncryptedSearch {
-    rng: Mutex<DefaultRng>,
-}
+    /**
+     * Encrypt the provided message to the provided public key as a JWE in the compact serialization. The content is
+     * encrypted with AES-256-GCM under a key derived from a fresh plaintext, and that plaintext is recrypt encrypted to
+     * the public key and signed with the provided signing key as the JWE Encrypted Key. See src/jwe.rs for the format.
+     */
+    pub fn encryptJwe(
+        &mut self,
+        message: &JsValue,
+        to_public_key: &JsValue,
+        private_signing_key: &JsValue,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "encryptJwe",
+            &[
+                ("message", message),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.encrypt_jwe(
+                        &Zeroizing::new(util::js_value_to_bytes(message, "message")?),
+                        &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                        &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        false,
+                    )
+                })
+            },
+        )
+    }
 
-#[wasm_bindgen]
-impl EncryptedSearch {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> EncryptedSearch {
-        EncryptedSearch {
-            rng: Mutex::new(ReseedingRng::default()),
-        }
+    /**
+     * Same as encryptJwe, but returns the JWE in the general JSON serialization with a single recipient.
+     */
+    pub fn encryptJweJson(
+        &mut self,
+        message: &JsValue,
+        to_public_key: &JsValue,
+        private_signing_key: &JsValue,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "encryptJweJson",
+            &[
+                ("message", message),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.encrypt_jwe(
+                        &Zeroizing::new(util::js_value_to_bytes(message, "message")?),
+                        &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                        &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                        true,
+                    )
+                })
+            },
+        )
     }
 
     /**
-     * Hashes all possible tri-grams for the given string. The values will be prefixed with the partition_id and salt before being hashed.
+     * Transform the Encrypted Key of the provided JWE with the provided transform key. The content is left untouched and
+     * the JWE is returned in the serialization it was provided in.
      */
-    pub fn generateHashesForString(
-        &self,
-        s: &str,
-        salt: &[u8],
-        partition_id: Option<String>,
-    ) -> Result<Vec<u32>, JsError> {
-        Ok(generate_hashes_for_string(s, partition_id.as_deref(), salt)
-            .map(|x| x.into_iter().collect::<Vec<_>>())
-            .map_err(WasmError::new)?)
+    pub fn transformJwe(
+        &mut self,
+        jwe: &JsValue,
+        transform_key: &JsValue,
+        private_signing_key: &JsValue,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "transformJwe",
+            &[
+                ("jwe", jwe),
+                ("transformKey", transform_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.transform_jwe(
+                        &util::js_value_to_string(jwe, "jwe")?,
+                        util::js_object_to_transform_key(transform_key, "transformKey")?,
+                        &util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?,
+                    )
+                })
+            },
+        )
     }
 
     /**
-     * Hashes all possible tri-grams for the given string. The values will be prefixed with the partition_id and salt before
-     * being hashed. This function will also add some random entries to the result to not expose how many tri-grams were actually found.
+     * Decrypt a JWE produced by encryptJwe or encryptJweJson, and possibly transformed by transformJwe, with the provided
+     * private key. Accepts the compact, general JSON and flattened JSON serializations. Returns the decrypted message.
      */
-    pub fn generateHashesForStringWithPadding(
+    pub fn decryptJwe(&self, jwe: &JsValue, private_key: &JsValue) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context(
+            "decryptJwe",
+            &[("jwe", jwe), ("privateKey", private_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    Ok(util::secret_bytes_to_js(&self.decrypt_jwe(
+                        &util::js_value_to_string(jwe, "jwe")?,
+                        &PrivateKey::new(util::js_to_fixed_32_bytes(private_key, "privateKey")?),
+                        "jwe",
+                    )?))
+                })
+            },
+        )
+    }
+
+    /**
+     * Export the provided recrypt key pair and Ed25519 signing key pair as one versioned, checksummed JSON bundle, along
+     * with their key IDs and creation time. createdAt is milliseconds since the Unix epoch and defaults to now. Fails if
+     * the key pair's public key isn't the one computePublicKey gives for its private key. See src/identity.rs for the format.
+     */
+    pub fn exportIdentity(
+        &mut self,
+        key_pair: &JsValue,
+        signing_key_pair: &JsValue,
+        created_at: &JsValue,
+    ) -> Result<JsString, JsError> {
+        diagnostics::with_failure_context(
+            "exportIdentity",
+            &[
+                ("keyPair", key_pair),
+                ("signingKeyPair", signing_key_pair),
+                ("createdAt", created_at),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let (private_key, public_key) =
+                        util::js_object_to_key_pair(key_pair, "keyPair")?;
+                    let identity = Identity {
+                        private_key,
+                        public_key,
+                        signing_key_pair: util::js_object_to_signing_keypair(
+                            signing_key_pair,
+                            "signingKeyPair",
+                        )?,
+                        created_at: if created_at.is_undefined() || created_at.is_null() {
+                            js_sys::Date::now() as u64
+                        } else {
+                            js_value_to_timestamp(created_at, "createdAt")?
+                        },
+                    };
+                    Ok(util::secret_string_to_js(&identity.to_json(
+                        |private_key| self.compute_public_key(private_key),
+                        "keyPair",
+                    )?))
+                })
+            },
+        )
+    }
+
+    /**
+     * Import a bundle produced by exportIdentity. The checksum is verified, both public keys are recomputed from their
+     * private keys and the key IDs are recomputed from the public keys before anything is returned. Returns an object with
+     * keyPair, signingKeyPair, createdAt, keyId and signingKeyId.
+     */
+    pub fn importIdentity(&self, bundle: &JsValue) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context("importIdentity", &[("bundle", bundle)], || {
+            secrets::with_cleared_stack(|| {
+                let identity = Identity::from_json(
+                    &Zeroizing::new(util::js_value_to_string(bundle, "bundle")?),
+                    |private_key| self.compute_public_key(private_key),
+                    "bundle",
+                )?;
+                let key_id = jwk::public_key_thumbprint(&identity.public_key);
+                let signing_public_key = *identity.signing_key_pair.public_key().bytes();
+                Ok(util::new_js_object(&[
+                    (
+                        "keyPair",
+                        util::key_pair_to_js_object(identity.private_key, identity.public_key)
+                            .into(),
+                    ),
+                    (
+                        "signingKeyPair",
+                        util::signing_keys_to_js_object(identity.signing_key_pair).into(),
+                    ),
+                    ("createdAt", (identity.created_at as f64).into()),
+                    ("keyId", key_id.into()),
+                    (
+                        "signingKeyId",
+                        jwk::signing_public_key_thumbprint(&signing_public_key).into(),
+                    ),
+                ]))
+            })
+        })
+    }
+
+    /**
+     * Import a JWK object produced by exportJwk. Returns a public key, key pair or signing key pair object depending on the
+     * JWK, validated the same way as the object forms. The public key of a recrypt private key is recomputed and must match
+     * the one in the JWK.
+     */
+    pub fn importJwk(&self, jwk: &JsValue) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context("importJwk", &[("jwk", jwk)], || {
+            secrets::with_cleared_stack(|| {
+                let key = jwk::jwk_to_key(
+                    jwk,
+                    |private_key| self.compute_public_key(private_key),
+                    "jwk",
+                )?;
+                Ok(key.into())
+            })
+        })
+    }
+
+    /**
+     * Encrypt the provided private key, and the Ed25519 signing key if one is provided, under a key derived from the
+     * password with Argon2id. The password is a string or bytes. options can set memoryCost (in KiB), iterations and
+     * parallelism for Argon2id, which default to 19456, 2 and 1. Returns a versioned binary blob which records the
+     * Argon2id parameters and the public key. See src/password.rs for the format.
+     */
+    pub fn wrapPrivateKeyWithPassword(
+        &mut self,
+        private_key: &JsValue,
+        password: &JsValue,
+        private_signing_key: &JsValue,
+        options: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context(
+            "wrapPrivateKeyWithPassword",
+            &[
+                ("privateKey", private_key),
+                ("password", password),
+                ("privateSigningKey", private_signing_key),
+                ("options", options),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let private_key =
+                        PrivateKey::new(util::js_to_fixed_32_bytes(private_key, "privateKey")?);
+                    let signing_key_pair =
+                        if private_signing_key.is_undefined() || private_signing_key.is_null() {
+                            None
+                        } else {
+                            Some(util::js_to_signing_keypair(
+                                private_signing_key,
+                                "privateSigningKey",
+                            )?)
+                        };
+                    self.wrap_with_password(
+                        &private_key,
+                        &self.compute_public_key(&private_key)?,
+                        signing_key_pair.as_ref(),
+                        password,
+                        options,
+                    )
+                })
+            },
+        )
+    }
+
+    /**
+     * Decrypt a blob produced by wrapPrivateKeyWithPassword with the password. The public key is recomputed with
+     * computePublicKey and must match the one recorded when the key was wrapped. Returns an object with keyPair, and
+     * signingKeyPair if a signing key was wrapped. Fails with DecryptFailed if the password is wrong or the blob was modified.
+     */
+    pub fn unwrapPrivateKeyWithPassword(
         &self,
-        s: &str,
-        salt: &[u8],
-        partition_id: Option<String>,
-    ) -> Result<Vec<u32>, JsError> {
-        Ok(
-            generate_hashes_for_string_with_padding(s, partition_id.as_deref(), salt, &self.rng)
-                .map(|x| x.into_iter().collect::<Vec<_>>())
-                .map_err(WasmError::new)?,
+        wrapped_key: &JsValue,
+        password: &JsValue,
+    ) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context(
+            "unwrapPrivateKeyWithPassword",
+            &[("wrappedKey", wrapped_key), ("password", password)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let keys = PasswordWrappedKeys::unwrap(
+                        &util::js_value_to_bytes(wrapped_key, "wrappedKey")?,
+                        &password::js_value_to_password(password, "password")?,
+                        |private_key| self.compute_public_key(private_key),
+                        "wrappedKey",
+                    )?;
+                    let mut properties = vec![(
+                        "keyPair",
+                        util::key_pair_to_js_object(keys.private_key, keys.public_key).into(),
+                    )];
+                    if let Some(signing_key_pair) = keys.signing_key_pair {
+                        properties.push((
+                            "signingKeyPair",
+                            util::signing_keys_to_js_object(signing_key_pair).into(),
+                        ));
+                    }
+                    Ok(util::new_js_object(&properties))
+                })
+            },
         )
     }
 
-    /// Generate a version of the input string where each character has been latinized using the
-    /// same function as our tokenization routines.
-    pub fn transliterateString(s: &str) -> String {
-        transliterate_string(s)
+    /**
+     * Generate a new Recrypt key pair whose private key stays in WASM memory. Returns an object with the PrivateKeyHandle
+     * as privateKey and the public key object as publicKey.
+     */
+    pub fn generateKeyPairHandle(&mut self) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context("generateKeyPairHandle", &[], || {
+            secrets::with_cleared_stack(|| {
+                let (key, public_key) = self.api.generate_key_pair().map_err(WasmError::new)?;
+                Ok(util::new_js_object(&[
+                    (
+                        "privateKey",
+                        PrivateKeyHandle {
+                            key: Box::new(key),
+                            public_key,
+                        }
+                        .into(),
+                    ),
+                    (
+                        "publicKey",
+                        util::public_key_to_js_object(public_key).into(),
+                    ),
+                ]))
+            })
+        })
     }
-}
 
-/**
- * Hash the provided transform key into a buffer of bytes. The various transform key object fields are concatenated
- * in a specific order in order for transform keys to be signed over.
- */
-#[wasm_bindgen]
-pub fn transformKeyToBytes256(transform_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
-    let transform_key_js: util::JsTransformKey =
-        transform_key_obj.into_serde().map_err(WasmError::new)?;
-    Ok(util::js_object_to_transform_key(transform_key_js)?.to_bytes())
-}
+    /**
+     * Move the provided private key bytes into a PrivateKeyHandle. The caller is responsible for clearing their own copy.
+     */
+    pub fn importPrivateKey(&mut self, private_key: &JsValue) -> Result<PrivateKeyHandle, JsError> {
+        diagnostics::with_failure_context(
+            "importPrivateKey",
+            &[("privateKey", private_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.new_private_key_handle(PrivateKey::new(util::js_to_fixed_32_bytes(
+                        private_key,
+                        "privateKey",
+                    )?))
+                })
+            },
+        )
+    }
 
-/**
- * Augment the provided transform key with the provided private key. Returns an augmented TransformKey object.
- */
-#[wasm_bindgen]
-pub fn augmentTransformKey256(
-    transform_key_obj: &JsValue,
-    private_key: &[u8],
-) -> Result<JsValue, JsError> {
-    let transform_key_js: util::JsTransformKey =
-        transform_key_obj.into_serde().map_err(WasmError::new)?;
+    /**
+     * Export the private key in the provided handle by encrypting it to the provided public key, signed with the provided
+     * signing key. Returns a JWE in the compact serialization, the same format as encryptJwe, so the wrapped key can be
+     * delegated with transformJwe. This and wrapPrivateKeyWithPasswordWithHandle are the only ways to get a private key
+     * back out of a handle.
+     */
+    pub fn wrapPrivateKey(
+        &mut self,
+        private_key: &PrivateKeyHandle,
+        to_public_key: &JsValue,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "wrapPrivateKey",
+            &[
+                ("privateKey", private_key),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.encrypt_jwe(
+                        private_key.key.bytes(),
+                        &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                        &private_signing_key.key_pair,
+                        false,
+                    )
+                })
+            },
+        )
+    }
 
-    let augmented_transform_key = util::js_object_to_transform_key(transform_key_js)?
-        .augment(&PrivateKey::new(util::slice_to_fixed_32_bytes(
-            private_key,
-            "privateKey",
-        )))
-        .map_err(WasmError::new)?;
-    Ok(
-        JsValue::from_serde(&util::transform_key_to_js_object(augmented_transform_key))
-            .map_err(WasmError::new)?,
-    )
-}
+    /**
+     * Decrypt a key wrapped by wrapPrivateKey, possibly after transformJwe, with the provided handle and return it as a
+     * new PrivateKeyHandle.
+     */
+    pub fn unwrapPrivateKey(
+        &mut self,
+        wrapped_key: &JsValue,
+        unwrapping_key: &PrivateKeyHandle,
+    ) -> Result<PrivateKeyHandle, JsError> {
+        diagnostics::with_failure_context(
+            "unwrapPrivateKey",
+            &[
+                ("wrappedKey", wrapped_key),
+                ("unwrappingKey", unwrapping_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let key = self.decrypt_jwe(
+                        &util::js_value_to_string(wrapped_key, "wrappedKey")?,
+                        &unwrapping_key.key,
+                        "wrappedKey",
+                    )?;
+                    self.new_private_key_handle(PrivateKey::new(util::slice_to_fixed_32_bytes(
+                        &key,
+                        "wrappedKey",
+                    )?))
+                })
+            },
+        )
+    }
 
-/**
- * Augment the provided public key with the other provided public key. Returns a new augmented PublicKey object.
- */
-#[wasm_bindgen]
-pub fn augmentPublicKey256(
-    current_public_key_obj: &JsValue,
-    other_public_key_obj: &JsValue,
-) -> Result<JsValue, JsError> {
-    let current_public_key_js: util::JsPublicKey = current_public_key_obj
-        .into_serde()
-        .map_err(WasmError::new)?;
-    let other_public_key_js: util::JsPublicKey =
-        other_public_key_obj.into_serde().map_err(WasmError::new)?;
+    /**
+     * Same as wrapPrivateKeyWithPassword without a signing key, but takes the private key as a PrivateKeyHandle.
+     */
+    pub fn wrapPrivateKeyWithPasswordWithHandle(
+        &mut self,
+        private_key: &PrivateKeyHandle,
+        password: &JsValue,
+        options: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context(
+            "wrapPrivateKeyWithPasswordWithHandle",
+            &[
+                ("privateKey", private_key),
+                ("password", password),
+                ("options", options),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.wrap_with_password(
+                        &private_key.key,
+                        &private_key.public_key,
+                        None,
+                        password,
+                        options,
+                    )
+                })
+            },
+        )
+    }
 
-    let augmented_public_key = util::js_object_to_public_key(&current_public_key_js)?
-        .augment(&util::js_object_to_public_key(&other_public_key_js)?)
-        .map_err(WasmError::new)?;
+    /**
+     * Same as wrapPrivateKeyWithPassword, but takes the private key as a PrivateKeyHandle and the signing key as a
+     * SigningKeyHandle.
+     */
+    pub fn wrapPrivateKeyWithPasswordWithHandles(
+        &mut self,
+        private_key: &PrivateKeyHandle,
+        password: &JsValue,
+        private_signing_key: &SigningKeyHandle,
+        options: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context(
+            "wrapPrivateKeyWithPasswordWithHandles",
+            &[
+                ("privateKey", private_key),
+                ("password", password),
+                ("privateSigningKey", private_signing_key),
+                ("options", options),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.wrap_with_password(
+                        &private_key.key,
+                        &private_key.public_key,
+                        Some(&private_signing_key.key_pair),
+                        password,
+                        options,
+                    )
+                })
+            },
+        )
+    }
 
-    Ok(
-        JsValue::from_serde(&util::public_key_to_js_object(augmented_public_key))
-            .map_err(WasmError::new)?,
-    )
-}
+    /**
+     * Same as unwrapPrivateKeyWithPassword, but the keys stay in WASM memory. Returns an object with keyPair, whose
+     * privateKey is a PrivateKeyHandle, and signingKey, a SigningKeyHandle, if a signing key was wrapped.
+     */
+    pub fn unwrapPrivateKeyHandleWithPassword(
+        &self,
+        wrapped_key: &JsValue,
+        password: &JsValue,
+    ) -> Result<JsValue, JsError> {
+        diagnostics::with_failure_context(
+            "unwrapPrivateKeyHandleWithPassword",
+            &[("wrappedKey", wrapped_key), ("password", password)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let keys = PasswordWrappedKeys::unwrap(
+                        &util::js_value_to_bytes(wrapped_key, "wrappedKey")?,
+                        &password::js_value_to_password(password, "password")?,
+                        |private_key| self.compute_public_key(private_key),
+                        "wrappedKey",
+                    )?;
+                    let key_pair = util::new_js_object(&[
+                        (
+                            "privateKey",
+                            PrivateKeyHandle {
+                                key: Box::new(keys.private_key),
+                                public_key: keys.public_key,
+                            }
+                            .into(),
+                        ),
+                        (
+                            "publicKey",
+                            util::public_key_to_js_object(keys.public_key).into(),
+                        ),
+                    ]);
+                    let mut properties = vec![("keyPair", key_pair)];
+                    if let Some(signing_key_pair) = keys.signing_key_pair {
+                        properties.push((
+                            "signingKey",
+                            SigningKeyHandle {
+                                key_pair: Box::new(signing_key_pair),
+                            }
+                            .into(),
+                        ));
+                    }
+                    Ok(util::new_js_object(&properties))
+                })
+            },
+        )
+    }
 
-/**
- * Add the two provided private keys together and return the bytes of a new PrivateKey.
- */
-#[wasm_bindgen]
-pub fn addPrivateKeys(private_key_a: &[u8], private_key_b: &[u8]) -> Result<Vec<u8>, JsError> {
-    let pubKeyA = PrivateKey::new(util::slice_to_fixed_32_bytes(private_key_a, "privateKeyA"));
-    let pubKeyB = PrivateKey::new(util::slice_to_fixed_32_bytes(private_key_b, "privateKeyB"));
-    Ok(pubKeyA.augment_plus(&pubKeyB).bytes().to_vec())
-}
+    /**
+     * Same as decryptWasm, but takes the private key as a PrivateKeyHandle.
+     */
+    pub fn decryptWithHandle(
+        &self,
+        encrypted_value: &WasmEncryptedValue,
+        private_key: &PrivateKeyHandle,
+    ) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context(
+            "decryptWithHandle",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("privateKey", private_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let decrypted_value = self
+                        .api
+                        .decrypt(encrypted_value.value.clone(), &private_key.key)
+                        .map_err(WasmError::new)?;
+                    Ok(util::secret_bytes_to_js(decrypted_value.bytes()))
+                })
+            },
+        )
+    }
 
-/**
- * Subtract the first provided private key from the second provided private key. Returns the bytes of a new PrivateKey.
+    /**
+     * Same as generateTransformKeyWasm, but takes the private key as a PrivateKeyHandle and the signing key as a
+     * SigningKeyHandle.
+     */
+    pub fn generateTransformKeyWithHandles(
+        &mut self,
+        from_private_key: &PrivateKeyHandle,
+        to_public_key: &WasmPublicKey,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<WasmTransformKey, JsError> {
+        diagnostics::with_failure_context(
+            "generateTransformKeyWithHandles",
+            &[
+                ("fromPrivateKey", from_private_key),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transform_key = self
+                        .api
+                        .generate_transform_key(
+                            &from_private_key.key,
+                            &to_public_key.key,
+                            &private_signing_key.key_pair,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmTransformKey { key: transform_key })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as schnorrSign, but takes the private key as a PrivateKeyHandle.
+     */
+    pub fn schnorrSignWithHandle(
+        &mut self,
+        private_key: &PrivateKeyHandle,
+        public_key_obj: &JsValue,
+        message: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context(
+            "schnorrSignWithHandle",
+            &[
+                ("privateKey", private_key),
+                ("publicKeyObj", public_key_obj),
+                ("message", message),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let message = util::js_value_to_bytes(message, "message")?;
+                    let signature = self.api.schnorr_sign(
+                        &private_key.key,
+                        &util::js_object_to_public_key(public_key_obj, "publicKey")?,
+                        &util::HashableSlice(&message),
+                    );
+                    Ok(signature.bytes().to_vec())
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as decryptJwe, but takes the private key as a PrivateKeyHandle.
+     */
+    pub fn decryptJweWithHandle(
+        &self,
+        jwe: &JsValue,
+        private_key: &PrivateKeyHandle,
+    ) -> Result<Uint8Array, JsError> {
+        diagnostics::with_failure_context(
+            "decryptJweWithHandle",
+            &[("jwe", jwe), ("privateKey", private_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    Ok(util::secret_bytes_to_js(&self.decrypt_jwe(
+                        &util::js_value_to_string(jwe, "jwe")?,
+                        &private_key.key,
+                        "jwe",
+                    )?))
+                })
+            },
+        )
+    }
+
+    /**
+     * Generate a new Ed25519 signing key pair which stays in WASM memory.
+     */
+    pub fn generateSigningKeyHandle(&mut self) -> SigningKeyHandle {
+        secrets::with_cleared_stack(|| SigningKeyHandle {
+            key_pair: Box::new(self.api.generate_ed25519_key_pair()),
+        })
+    }
+
+    /**
+     * Parse and check the provided 32 byte seed or 64 byte private signing key once and move it into a SigningKeyHandle.
+     * The caller is responsible for clearing their own copy.
+     */
+    pub fn importSigningKey(
+        &mut self,
+        private_signing_key: &JsValue,
+    ) -> Result<SigningKeyHandle, JsError> {
+        diagnostics::with_failure_context(
+            "importSigningKey",
+            &[("privateSigningKey", private_signing_key)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    Ok(SigningKeyHandle {
+                        key_pair: Box::new(util::js_to_signing_keypair(
+                            private_signing_key,
+                            "privateSigningKey",
+                        )?),
+                    })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as ed25519Sign, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn ed25519SignWithHandle(
+        &mut self,
+        signing_key: &SigningKeyHandle,
+        message: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        diagnostics::with_failure_context(
+            "ed25519SignWithHandle",
+            &[("signingKey", signing_key), ("message", message)],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let message = util::js_value_to_bytes(message, "message")?;
+                    Ok(util::ed25519_sign(&signing_key.key_pair, &message).to_vec())
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as encryptWasm, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn encryptWithHandle(
+        &mut self,
+        plaintext: &JsValue,
+        to_public_key: &WasmPublicKey,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<WasmEncryptedValue, JsError> {
+        diagnostics::with_failure_context(
+            "encryptWithHandle",
+            &[
+                ("plaintext", plaintext),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let encrypted_value = self
+                        .api
+                        .encrypt(
+                            &Plaintext::new(util::js_to_fixed_384_bytes(plaintext, "plaintext")?),
+                            &to_public_key.key,
+                            &private_signing_key.key_pair,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmEncryptedValue {
+                        value: encrypted_value,
+                    })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as transformWasm, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn transformWithHandle(
+        &mut self,
+        encrypted_value: &WasmEncryptedValue,
+        transform_key: &WasmTransformKey,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<WasmEncryptedValue, JsError> {
+        diagnostics::with_failure_context(
+            "transformWithHandle",
+            &[
+                ("encryptedValue", encrypted_value),
+                ("transformKey", transform_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    let transformed_encrypted_value = self
+                        .api
+                        .transform(
+                            encrypted_value.value.clone(),
+                            transform_key.key.clone(),
+                            &private_signing_key.key_pair,
+                        )
+                        .map_err(WasmError::new)?;
+                    Ok(WasmEncryptedValue {
+                        value: transformed_encrypted_value,
+                    })
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as encryptJwe, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn encryptJweWithHandle(
+        &mut self,
+        message: &JsValue,
+        to_public_key: &JsValue,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "encryptJweWithHandle",
+            &[
+                ("message", message),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.encrypt_jwe(
+                        &Zeroizing::new(util::js_value_to_bytes(message, "message")?),
+                        &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                        &private_signing_key.key_pair,
+                        false,
+                    )
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as encryptJweJson, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn encryptJweJsonWithHandle(
+        &mut self,
+        message: &JsValue,
+        to_public_key: &JsValue,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "encryptJweJsonWithHandle",
+            &[
+                ("message", message),
+                ("toPublicKey", to_public_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.encrypt_jwe(
+                        &Zeroizing::new(util::js_value_to_bytes(message, "message")?),
+                        &util::js_object_to_public_key(to_public_key, "toPublicKey")?,
+                        &private_signing_key.key_pair,
+                        true,
+                    )
+                })
+            },
+        )
+    }
+
+    /**
+     * Same as transformJwe, but takes the signing key as a SigningKeyHandle.
+     */
+    pub fn transformJweWithHandle(
+        &mut self,
+        jwe: &JsValue,
+        transform_key: &JsValue,
+        private_signing_key: &SigningKeyHandle,
+    ) -> Result<String, JsError> {
+        diagnostics::with_failure_context(
+            "transformJweWithHandle",
+            &[
+                ("jwe", jwe),
+                ("transformKey", transform_key),
+                ("privateSigningKey", private_signing_key),
+            ],
+            || {
+                secrets::with_cleared_stack(|| {
+                    self.transform_jwe(
+                        &util::js_value_to_string(jwe, "jwe")?,
+                        util::js_object_to_transform_key(transform_key, "transformKey")?,
+                        &private_signing_key.key_pair,
+                    )
+                })
+            },
+        )
+    }
+}
+
+impl Api256 {
+    fn encrypt_jwe(
+        &mut self,
+        message: &[u8],
+        to_public_key: &PublicKey,
+        signing_key_pair: &SigningKeypair,
+        json: bool,
+    ) -> Result<String, JsError> {
+        let plaintext = self.api.gen_plaintext();
+        let encrypted_key = self
+            .api
+            .encrypt(&plaintext, to_public_key, signing_key_pair)
+            .map_err(WasmError::new)?;
+        let cek = self.api.derive_symmetric_key(&plaintext);
+        Ok(jwe::Jwe::encrypt(cek.bytes(), encrypted_key, message, json)?.serialize())
+    }
+
+    fn transform_jwe(
+        &mut self,
+        jwe: &str,
+        transform_key: TransformKey,
+        signing_key_pair: &SigningKeypair,
+    ) -> Result<String, JsError> {
+        let mut jwe = jwe::Jwe::parse(jwe, "jwe")?;
+        jwe.encrypted_key = self
+            .api
+            .transform(jwe.encrypted_key, transform_key, signing_key_pair)
+            .map_err(WasmError::new)?;
+        Ok(jwe.serialize())
+    }
+
+    fn decrypt_jwe(
+        &self,
+        jwe: &str,
+        private_key: &PrivateKey,
+        field: &str,
+    ) -> Result<Zeroizing<Vec<u8>>, JsError> {
+        let jwe = jwe::Jwe::parse(jwe, field)?;
+        let plaintext = self
+            .api
+            .decrypt(jwe.encrypted_key.clone(), private_key)
+            .map_err(WasmError::new)?;
+        let cek = self.api.derive_symmetric_key(&plaintext);
+        Ok(jwe.decrypt(cek.bytes(), field)?)
+    }
+
+    fn wrap_with_password(
+        &self,
+        private_key: &PrivateKey,
+        public_key: &PublicKey,
+        signing_key_pair: Option<&SigningKeypair>,
+        password: &JsValue,
+        options: &JsValue,
+    ) -> Result<Vec<u8>, JsError> {
+        let costs = Argon2Costs::from_js(options, "options")?;
+        let password = password::js_value_to_password(password, "password")?;
+        Ok(PasswordWrappedKeys::wrap(
+            private_key,
+            public_key,
+            signing_key_pair,
+            &password,
+            &costs,
+        )?)
+    }
+
+    fn new_private_key_handle(&self, key: PrivateKey) -> Result<PrivateKeyHandle, JsError> {
+        let public_key = self.compute_public_key(&key)?;
+        Ok(PrivateKeyHandle {
+            key: Box::new(key),
+            public_key,
+        })
+    }
+
+    fn compute_public_key(&self, private_key: &PrivateKey) -> Result<PublicKey, JsError> {
+        Ok(self
+            .api
+            .compute_public_key(private_key)
+            .map_err(WasmError::new)?)
+    }
+}
+
+/**
+ * Read a timestamp in milliseconds since the Unix epoch, which must be a non-negative integer JS number.
+ */
+fn js_value_to_timestamp(value: &JsValue, field: &str) -> Result<u64, BindingError> {
+    match value.as_f64() {
+        Some(timestamp)
+            if timestamp >= 0.0
+                && timestamp.fract() == 0.0
+                && timestamp <= js_sys::Number::MAX_SAFE_INTEGER =>
+        {
+            Ok(timestamp as u64)
+        }
+        _ => Err(BindingError::InvalidType {
+            field: field.to_string(),
+            expected: "a non-negative integer number of milliseconds",
+        }),
+    }
+}
+
+impl Default for Api256 {
+    fn default() -> Self {
+        Api256::new()
+    }
+}
+
+#[wasm_bindgen]
+pub struct EncryptedSearch {
+    rng: Mutex<DefaultRng>,
+}
+
+#[wasm_bindgen]
+impl EncryptedSearch {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> EncryptedSearch {
+        EncryptedSearch {
+            rng: Mutex::new(ReseedingRng::default()),
+        }
+    }
+
+    /**
+     * Hashes all possible tri-grams for the given string. The values will be prefixed with the partition_id and salt before being hashed.
+     */
+    pub fn generateHashesForString(
+        &self,
+        s: &str,
+        salt: &JsValue,
+        partition_id: Option<String>,
+    ) -> Result<Vec<u32>, JsError> {
+        diagnostics::with_failure_context(
+            "generateHashesForString",
+            &[("s", &s), ("salt", salt), ("partitionId", &partition_id)],
+            || {
+                let salt = util::js_value_to_bytes(salt, "salt")?;
+                Ok(
+                    generate_hashes_for_string(s, partition_id.as_deref(), &salt)
+                        .map(|x| x.into_iter().collect::<Vec<_>>())
+                        .map_err(util::BindingError::InvalidSearchInput)?,
+                )
+            },
+        )
+    }
+
+    /**
+     * Hashes all possible tri-grams for the given string. The values will be prefixed with the partition_id and salt before
+     * being hashed. This function will also add some random entries to the result to not expose how many tri-grams were actually found.
+     */
+    pub fn generateHashesForStringWithPadding(
+        &self,
+        s: &str,
+        salt: &JsValue,
+        partition_id: Option<String>,
+    ) -> Result<Vec<u32>, JsError> {
+        diagnostics::with_failure_context(
+            "generateHashesForStringWithPadding",
+            &[("s", &s), ("salt", salt), ("partitionId", &partition_id)],
+            || {
+                let salt = util::js_value_to_bytes(salt, "salt")?;
+                Ok(generate_hashes_for_string_with_padding(
+                    s,
+                    partition_id.as_deref(),
+                    &salt,
+                    &self.rng,
+                )
+                .map(|x| x.into_iter().collect::<Vec<_>>())
+                .map_err(util::BindingError::InvalidSearchInput)?)
+            },
+        )
+    }
+
+    /// Generate a version of the input string where each character has been latinized using the
+    /// same function as our tokenization routines.
+    pub fn transliterateString(s: &str) -> String {
+        transliterate_string(s)
+    }
+}
+
+impl Default for EncryptedSearch {
+    fn default() -> Self {
+        EncryptedSearch::new()
+    }
+}
+
+/**
+ * Hash the provided transform key into a buffer of bytes. The various transform key object fields are concatenated
+ * in a specific order in order for transform keys to be signed over.
+ */
+#[wasm_bindgen]
+pub fn transformKeyToBytes256(transform_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    Ok(util::js_object_to_transform_key(transform_key_obj, "transformKey")?.to_bytes())
+}
+
+/**
+ * Augment the provided transform key with the provided private key. Returns an augmented TransformKey object.
+ */
+#[wasm_bindgen]
+pub fn augmentTransformKey256(
+    transform_key_obj: &JsValue,
+    private_key: &JsValue,
+) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let augmented_transform_key =
+            util::js_object_to_transform_key(transform_key_obj, "transformKey")?
+                .augment(&PrivateKey::new(util::js_to_fixed_32_bytes(
+                    private_key,
+                    "privateKey",
+                )?))
+                .map_err(WasmError::new)?;
+        Ok(util::transform_key_to_js_object(augmented_transform_key).into())
+    })
+}
+
+/**
+ * Augment the provided public key with the other provided public key. Returns a new augmented PublicKey object.
+ */
+#[wasm_bindgen]
+pub fn augmentPublicKey256(
+    current_public_key_obj: &JsValue,
+    other_public_key_obj: &JsValue,
+) -> Result<JsValue, JsError> {
+    let augmented_public_key =
+        util::js_object_to_public_key(current_public_key_obj, "currentPublicKey")?
+            .augment(&util::js_object_to_public_key(
+                other_public_key_obj,
+                "otherPublicKey",
+            )?)
+            .map_err(WasmError::new)?;
+
+    Ok(util::public_key_to_js_object(augmented_public_key).into())
+}
+
+/**
+ * Add the two provided private keys together and return the bytes of a new PrivateKey.
+ */
+#[wasm_bindgen]
+pub fn addPrivateKeys(
+    private_key_a: &JsValue,
+    private_key_b: &JsValue,
+) -> Result<Uint8Array, JsError> {
+    secrets::with_cleared_stack(|| {
+        let pubKeyA = PrivateKey::new(util::js_to_fixed_32_bytes(private_key_a, "privateKeyA")?);
+        let pubKeyB = PrivateKey::new(util::js_to_fixed_32_bytes(private_key_b, "privateKeyB")?);
+        Ok(util::secret_bytes_to_js(
+            pubKeyA.augment_plus(&pubKeyB).bytes(),
+        ))
+    })
+}
+
+/**
+ * Subtract the first provided private key from the second provided private key. Returns the bytes of a new PrivateKey.
+ */
+#[wasm_bindgen]
+pub fn subtractPrivateKeys(
+    private_key_a: &JsValue,
+    private_key_b: &JsValue,
+) -> Result<Uint8Array, JsError> {
+    secrets::with_cleared_stack(|| {
+        let pubKeyA = PrivateKey::new(util::js_to_fixed_32_bytes(private_key_a, "privateKeyA")?);
+        let pubKeyB = PrivateKey::new(util::js_to_fixed_32_bytes(private_key_b, "privateKeyB")?);
+        Ok(util::secret_bytes_to_js(
+            pubKeyA.augment_minus(&pubKeyB).bytes(),
+        ))
+    })
+}
+
+/**
+ * Describe the provided encrypted value object without decrypting it. Returns an object with the variant
+ * (`EncryptedOnceValue` or `TransformedValue`), the transformLevel (number of transform hops), transformedWith (the
+ * ephemeralPublicKey of the transform key used for each hop, in the order the transforms happened) and the
+ * publicSigningKey that signed the value.
+ */
+#[wasm_bindgen]
+pub fn inspectEncryptedValue(encrypted_value_obj: &JsValue) -> Result<JsValue, JsError> {
+    let encrypted_value =
+        util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?;
+    Ok(util::encrypted_value_to_js_info(&encrypted_value).into())
+}
+
+/**
+ * Encode the provided encrypted value object, either encrypted once or transformed any number of times, into a versioned
+ * binary form which is far smaller than the object form. See src/encoding.rs for the layout.
+ */
+#[wasm_bindgen]
+pub fn encodeEncryptedValue(encrypted_value_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let encrypted_value =
+        util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?;
+    Ok(encoding::encode_encrypted_value(&encrypted_value))
+}
+
+/**
+ * Decode bytes produced by encodeEncryptedValue back into an encrypted value object. Throws if the bytes are truncated,
+ * have trailing data, are an unsupported version or contain invalid public keys.
+ */
+#[wasm_bindgen]
+pub fn decodeEncryptedValue(encoded_value: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_value, "encodedValue")?;
+    let encrypted_value = encoding::decode_encrypted_value(&bytes, "encodedValue")?;
+    Ok(util::encrypted_value_to_js_object(encrypted_value).into())
+}
+
+/**
+ * Encode the provided transform key object into a versioned binary form. Unlike transformKeyToBytes256, the result can
+ * be decoded back into a transform key with decodeTransformKey. See src/encoding.rs for the layout.
+ */
+#[wasm_bindgen]
+pub fn encodeTransformKey(transform_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let transform_key = util::js_object_to_transform_key(transform_key_obj, "transformKey")?;
+    Ok(encoding::encode_transform_key(&transform_key))
+}
+
+/**
+ * Decode bytes produced by encodeTransformKey back into a transform key object. Throws if the bytes are not exactly one
+ * encoded transform key, are an unsupported version or contain invalid public keys.
+ */
+#[wasm_bindgen]
+pub fn decodeTransformKey(encoded_key: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_key, "encodedKey")?;
+    let transform_key = encoding::decode_transform_key(&bytes, "encodedKey")?;
+    Ok(util::transform_key_to_js_object(transform_key).into())
+}
+
+/**
+ * Encode the provided encrypted value object as an EncryptedValue protobuf message. See proto/recrypt.proto for the
+ * schema.
+ */
+#[wasm_bindgen]
+pub fn encodeEncryptedValueProtobuf(encrypted_value_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let encrypted_value =
+        util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?;
+    Ok(protobuf::encode_encrypted_value(encrypted_value))
+}
+
+/**
+ * Decode an EncryptedValue protobuf message into an encrypted value object. The message gets the same validation as an
+ * encrypted value object passed to decrypt.
+ */
+#[wasm_bindgen]
+pub fn decodeEncryptedValueProtobuf(encoded_value: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_value, "encodedValue")?;
+    let encrypted_value = protobuf::decode_encrypted_value(&bytes, "encodedValue")?;
+    Ok(util::encrypted_value_to_js_object(encrypted_value).into())
+}
+
+/**
+ * Encode the provided transform key object as a TransformKey protobuf message. See proto/recrypt.proto for the schema.
+ */
+#[wasm_bindgen]
+pub fn encodeTransformKeyProtobuf(transform_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let transform_key = util::js_object_to_transform_key(transform_key_obj, "transformKey")?;
+    Ok(protobuf::encode_transform_key(transform_key))
+}
+
+/**
+ * Decode a TransformKey protobuf message into a transform key object. The message gets the same validation as a transform
+ * key object passed to transform.
+ */
+#[wasm_bindgen]
+pub fn decodeTransformKeyProtobuf(encoded_key: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_key, "encodedKey")?;
+    let transform_key = protobuf::decode_transform_key(&bytes, "encodedKey")?;
+    Ok(util::transform_key_to_js_object(transform_key).into())
+}
+
+/**
+ * Encode the provided public key object as a CBOR map with x/y byte strings.
+ */
+#[wasm_bindgen]
+pub fn encodePublicKeyCbor(public_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let public_key = util::js_object_to_public_key(public_key_obj, "publicKey")?;
+    Ok(cbor::encode_public_key(public_key)?)
+}
+
+/**
+ * Decode a CBOR encoded public key into a public key object, validating that it is on the curve.
+ */
+#[wasm_bindgen]
+pub fn decodePublicKeyCbor(encoded_key: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_key, "encodedKey")?;
+    let public_key = cbor::decode_public_key(&bytes, "encodedKey")?;
+    Ok(util::public_key_to_js_object(public_key).into())
+}
+
+/**
+ * Encode the provided encrypted value object as a CBOR map with the same keys as the object.
+ */
+#[wasm_bindgen]
+pub fn encodeEncryptedValueCbor(encrypted_value_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let encrypted_value =
+        util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?;
+    Ok(cbor::encode_encrypted_value(encrypted_value)?)
+}
+
+/**
+ * Decode a CBOR encoded encrypted value into an encrypted value object.
+ */
+#[wasm_bindgen]
+pub fn decodeEncryptedValueCbor(encoded_value: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_value, "encodedValue")?;
+    let encrypted_value = cbor::decode_encrypted_value(&bytes, "encodedValue")?;
+    Ok(util::encrypted_value_to_js_object(encrypted_value).into())
+}
+
+/**
+ * Encode the provided transform key object as a CBOR map with the same keys as the object.
+ */
+#[wasm_bindgen]
+pub fn encodeTransformKeyCbor(transform_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let transform_key = util::js_object_to_transform_key(transform_key_obj, "transformKey")?;
+    Ok(cbor::encode_transform_key(transform_key)?)
+}
+
+/**
+ * Decode a CBOR encoded transform key into a transform key object.
+ */
+#[wasm_bindgen]
+pub fn decodeTransformKeyCbor(encoded_key: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(encoded_key, "encodedKey")?;
+    let transform_key = cbor::decode_transform_key(&bytes, "encodedKey")?;
+    Ok(util::transform_key_to_js_object(transform_key).into())
+}
+
+/**
+ * Encode the provided signing key pair object as a CBOR map with privateKey/publicKey byte strings.
+ */
+#[wasm_bindgen]
+pub fn encodeSigningKeyPairCbor(signing_key_pair_obj: &JsValue) -> Result<Uint8Array, JsError> {
+    secrets::with_cleared_stack(|| {
+        let signing_key_pair =
+            util::js_object_to_signing_keypair(signing_key_pair_obj, "signingKeyPair")?;
+        Ok(util::secret_bytes_to_js(&cbor::encode_signing_key_pair(
+            signing_key_pair,
+        )?))
+    })
+}
+
+/**
+ * Decode a CBOR encoded signing key pair into a signing key pair object. Throws if the public key doesn't match the
+ * private key.
+ */
+#[wasm_bindgen]
+pub fn decodeSigningKeyPairCbor(encoded_key_pair: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let bytes = Zeroizing::new(util::js_value_to_bytes(encoded_key_pair, "encodedKeyPair")?);
+        let signing_key_pair = cbor::decode_signing_key_pair(&bytes, "encodedKeyPair")?;
+        Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+    })
+}
+
+/**
+ * The additional authenticated data that content must be encrypted with before it is passed to encodeCoseEncrypt.
+ */
+#[wasm_bindgen]
+pub fn coseEncryptAad() -> Result<Vec<u8>, JsError> {
+    Ok(cbor::cose_encrypt_aad()?)
+}
+
+/**
+ * Wrap AES-256-GCM encrypted content in a COSE_Encrypt envelope. The encrypted DEK is the recrypt encrypted value whose
+ * plaintext the content key was derived from (e.g. via deriveSymmetricKey), the ciphertext is the AES-GCM output
+ * including the tag, and the iv is the 12 byte IV it was encrypted with. The content must have been encrypted with
+ * coseEncryptAad as its additional data. See src/cbor.rs for the layout.
+ */
+#[wasm_bindgen]
+pub fn encodeCoseEncrypt(
+    encrypted_dek_obj: &JsValue,
+    ciphertext: &JsValue,
+    iv: &JsValue,
+) -> Result<Vec<u8>, JsError> {
+    Ok(cbor::encode_cose_encrypt(cbor::CoseEncrypt {
+        encrypted_dek: util::js_object_to_encrypted_value(encrypted_dek_obj, "encryptedDek")?,
+        ciphertext: util::js_value_to_bytes(ciphertext, "ciphertext")?,
+        iv: util::js_to_fixed_12_bytes(iv, "iv")?,
+    })?)
+}
+
+/**
+ * Unwrap a COSE_Encrypt envelope produced by encodeCoseEncrypt into an object with the encryptedDek, ciphertext and iv.
+ */
+#[wasm_bindgen]
+pub fn decodeCoseEncrypt(envelope: &JsValue) -> Result<JsValue, JsError> {
+    let bytes = util::js_value_to_bytes(envelope, "envelope")?;
+    Ok(cbor::decode_cose_encrypt(&bytes, "envelope")?.into())
+}
+
+/**
+ * Serialize the provided public key object as versioned JSON with base64url coordinates.
+ */
+#[wasm_bindgen]
+pub fn publicKeyToJson(public_key_obj: &JsValue) -> Result<String, JsError> {
+    let public_key = util::js_object_to_public_key(public_key_obj, "publicKey")?;
+    Ok(json::public_key_to_json(public_key))
+}
+
+/**
+ * Parse versioned JSON produced by publicKeyToJson into a public key object, validating that it is on the curve.
+ */
+#[wasm_bindgen]
+pub fn publicKeyFromJson(json: &JsValue) -> Result<JsValue, JsError> {
+    let json = util::js_value_to_string(json, "json")?;
+    let public_key = json::public_key_from_json(&json, "json")?;
+    Ok(util::public_key_to_js_object(public_key).into())
+}
+
+/**
+ * Serialize the provided key pair object as versioned JSON with base64url fields.
+ */
+#[wasm_bindgen]
+pub fn keyPairToJson(key_pair_obj: &JsValue) -> Result<JsString, JsError> {
+    secrets::with_cleared_stack(|| {
+        let (private_key, public_key) = util::js_object_to_key_pair(key_pair_obj, "keyPair")?;
+        Ok(util::secret_string_to_js(&json::key_pair_to_json(
+            private_key,
+            public_key,
+        )))
+    })
+}
+
+/**
+ * Parse versioned JSON produced by keyPairToJson into a key pair object.
+ */
+#[wasm_bindgen]
+pub fn keyPairFromJson(json: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let json = Zeroizing::new(util::js_value_to_string(json, "json")?);
+        let (private_key, public_key) = json::key_pair_from_json(&json, "json")?;
+        Ok(util::key_pair_to_js_object(private_key, public_key).into())
+    })
+}
+
+/**
+ * Serialize the provided Ed25519 signing key pair object as versioned JSON with base64url fields.
+ */
+#[wasm_bindgen]
+pub fn signingKeyPairToJson(signing_key_pair_obj: &JsValue) -> Result<JsString, JsError> {
+    secrets::with_cleared_stack(|| {
+        let signing_key_pair =
+            util::js_object_to_signing_keypair(signing_key_pair_obj, "signingKeyPair")?;
+        Ok(util::secret_string_to_js(&json::signing_key_pair_to_json(
+            signing_key_pair,
+        )))
+    })
+}
+
+/**
+ * Parse versioned JSON produced by signingKeyPairToJson into a signing key pair object, verifying that the public key
+ * matches the private key.
+ */
+#[wasm_bindgen]
+pub fn signingKeyPairFromJson(json: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let json = Zeroizing::new(util::js_value_to_string(json, "json")?);
+        let signing_key_pair = json::signing_key_pair_from_json(&json, "json")?;
+        Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+    })
+}
+
+/**
+ * Serialize the provided transform key object as versioned JSON with base64url fields.
+ */
+#[wasm_bindgen]
+pub fn transformKeyToJson(transform_key_obj: &JsValue) -> Result<String, JsError> {
+    let transform_key = util::js_object_to_transform_key(transform_key_obj, "transformKey")?;
+    Ok(json::transform_key_to_json(transform_key))
+}
+
+/**
+ * Parse versioned JSON produced by transformKeyToJson into a transform key object.
+ */
+#[wasm_bindgen]
+pub fn transformKeyFromJson(json: &JsValue) -> Result<JsValue, JsError> {
+    let json = util::js_value_to_string(json, "json")?;
+    let transform_key = json::transform_key_from_json(&json, "json")?;
+    Ok(util::transform_key_to_js_object(transform_key).into())
+}
+
+/**
+ * Serialize the provided encrypted value object as versioned JSON with base64url fields.
+ */
+#[wasm_bindgen]
+pub fn encryptedValueToJson(encrypted_value_obj: &JsValue) -> Result<String, JsError> {
+    let encrypted_value =
+        util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?;
+    Ok(json::encrypted_value_to_json(encrypted_value))
+}
+
+/**
+ * Parse versioned JSON produced by encryptedValueToJson into an encrypted value object.
+ */
+#[wasm_bindgen]
+pub fn encryptedValueFromJson(json: &JsValue) -> Result<JsValue, JsError> {
+    let json = util::js_value_to_string(json, "json")?;
+    let encrypted_value = json::encrypted_value_from_json(&json, "json")?;
+    Ok(util::encrypted_value_to_js_object(encrypted_value).into())
+}
+
+/**
+ * Export the provided recrypt public key, recrypt key pair or Ed25519 signing key pair as a JWK object. Ed25519 key pairs
+ * use the standard OKP form and recrypt keys use the private RECRYPT key type. See src/jwk.rs for the members.
+ */
+#[wasm_bindgen]
+pub fn exportJwk(key: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| Ok(jwk::key_to_jwk(jwk::js_value_to_key(key, "key")?)))
+}
+
+/**
+ * Expand a 32 byte Ed25519 seed, the private key form most other libraries use, into a signing key pair object with the
+ * 64 byte seed and public key form of the private key.
+ */
+#[wasm_bindgen]
+pub fn expandEd25519Seed(seed: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let seed = util::js_to_fixed_32_bytes(seed, "seed")?;
+        let signing_key_pair = util::seed_to_signing_keypair(&seed, "seed")?;
+        Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+    })
+}
+
+/**
+ * Extract the 32 byte Ed25519 seed from the provided private signing key. Fails if the public half of a 64 byte key does
+ * not match its seed.
+ */
+#[wasm_bindgen]
+pub fn extractEd25519Seed(private_signing_key: &JsValue) -> Result<Uint8Array, JsError> {
+    secrets::with_cleared_stack(|| {
+        let signing_key_pair =
+            util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?;
+        Ok(util::secret_bytes_to_js(
+            util::signing_keypair_seed(&signing_key_pair).as_slice(),
+        ))
+    })
+}
+
+/**
+ * Export the provided Ed25519 private signing key as a DER encoded PKCS#8 PrivateKeyInfo, loadable by WebCrypto, Node and
+ * OpenSSL.
+ */
+#[wasm_bindgen]
+pub fn exportSigningKeyPkcs8(private_signing_key: &JsValue) -> Result<Uint8Array, JsError> {
+    secrets::with_cleared_stack(|| {
+        let signing_key_pair =
+            util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?;
+        Ok(util::secret_bytes_to_js(&pkcs8::encode_pkcs8(
+            &signing_key_pair,
+        )))
+    })
+}
+
+/**
+ * Same as exportSigningKeyPkcs8, but returns a PEM encoded "PRIVATE KEY" block.
+ */
+#[wasm_bindgen]
+pub fn exportSigningKeyPkcs8Pem(private_signing_key: &JsValue) -> Result<JsString, JsError> {
+    secrets::with_cleared_stack(|| {
+        let signing_key_pair =
+            util::js_to_signing_keypair(private_signing_key, "privateSigningKey")?;
+        Ok(util::secret_string_to_js(&pkcs8::encode_pkcs8_pem(
+            &signing_key_pair,
+        )))
+    })
+}
+
+/**
+ * Import a DER encoded PKCS#8 Ed25519 private key into a signing key pair object.
+ */
+#[wasm_bindgen]
+pub fn importSigningKeyPkcs8(pkcs8: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let bytes = Zeroizing::new(util::js_value_to_bytes(pkcs8, "pkcs8")?);
+        let signing_key_pair = pkcs8::decode_pkcs8(&bytes, "pkcs8")?;
+        Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+    })
+}
+
+/**
+ * Import a PEM encoded PKCS#8 Ed25519 private key into a signing key pair object.
+ */
+#[wasm_bindgen]
+pub fn importSigningKeyPkcs8Pem(pem: &JsValue) -> Result<JsValue, JsError> {
+    secrets::with_cleared_stack(|| {
+        let pem = Zeroizing::new(util::js_value_to_string(pem, "pem")?);
+        let signing_key_pair = pkcs8::decode_pkcs8_pem(&pem, "pem")?;
+        Ok(util::signing_keys_to_js_object(signing_key_pair).into())
+    })
+}
+
+/**
+ * Export the provided 32 byte Ed25519 public signing key as a DER encoded SubjectPublicKeyInfo.
+ */
+#[wasm_bindgen]
+pub fn exportSigningPublicKeySpki(public_signing_key: &JsValue) -> Result<Vec<u8>, JsError> {
+    let public_key = util::js_to_fixed_32_bytes(public_signing_key, "publicSigningKey")?;
+    Ok(pkcs8::encode_spki(&public_key))
+}
+
+/**
+ * Same as exportSigningPublicKeySpki, but returns a PEM encoded "PUBLIC KEY" block.
+ */
+#[wasm_bindgen]
+pub fn exportSigningPublicKeySpkiPem(public_signing_key: &JsValue) -> Result<String, JsError> {
+    let public_key = util::js_to_fixed_32_bytes(public_signing_key, "publicSigningKey")?;
+    Ok(pkcs8::encode_spki_pem(&public_key))
+}
+
+/**
+ * Import a DER encoded SubjectPublicKeyInfo holding an Ed25519 public key. Returns the 32 byte public signing key.
+ */
+#[wasm_bindgen]
+pub fn importSigningPublicKeySpki(spki: &JsValue) -> Result<Vec<u8>, JsError> {
+    let bytes = util::js_value_to_bytes(spki, "spki")?;
+    Ok(pkcs8::decode_spki(&bytes, "spki")?.to_vec())
+}
+
+/**
+ * Import a PEM encoded SubjectPublicKeyInfo holding an Ed25519 public key. Returns the 32 byte public signing key.
+ */
+#[wasm_bindgen]
+pub fn importSigningPublicKeySpkiPem(pem: &JsValue) -> Result<Vec<u8>, JsError> {
+    let pem = util::js_value_to_string(pem, "pem")?;
+    Ok(pkcs8::decode_spki_pem(&pem, "pem")?.to_vec())
+}
+
+/**
+ * Compress the provided public key into 33 bytes: a prefix of 2 or 3 for the parity of the y coordinate, followed by the x
+ * coordinate. Compressed public keys are accepted anywhere a public key object is.
+ */
+#[wasm_bindgen]
+pub fn compressPublicKey(public_key_obj: &JsValue) -> Result<Vec<u8>, JsError> {
+    let public_key = util::js_object_to_public_key(public_key_obj, "publicKey")?;
+    Ok(encoding::compress_public_key(&public_key).to_vec())
+}
+
+/**
+ * Convert a 33 byte compressed public key back into a public key object with x/y properties. The key gets the same on-curve
+ * validation as every other public key.
+ */
+#[wasm_bindgen]
+pub fn decompressPublicKey(compressed_public_key: &JsValue) -> Result<JsValue, JsError> {
+    let compressed = util::js_to_fixed_33_bytes(compressed_public_key, "compressedPublicKey")?;
+    let public_key = encoding::decompress_public_key(&compressed, "compressedPublicKey")?;
+    Ok(util::public_key_to_js_object(public_key).into())
+}
+
+/**
+ * Check that the provided public key object is well-formed and is a valid point on the curve. Returns an array of
+ * diagnostics, one for every problem found, which is empty if the public key is valid.
+ */
+#[wasm_bindgen]
+pub fn validatePublicKey(public_key_obj: &JsValue) -> Array {
+    util::validate_public_key(public_key_obj, "publicKey").to_js_array()
+}
+
+/**
+ * Returns whether the provided public key object is well-formed and is a valid point on the curve.
+ */
+#[wasm_bindgen]
+pub fn isValidPublicKey(public_key_obj: &JsValue) -> bool {
+    util::validate_public_key(public_key_obj, "publicKey").is_empty()
+}
+
+/**
+ * Check that the provided transform key object is well-formed without using it. Returns an array of diagnostics, one for
+ * every problem found, which is empty if the transform key is valid.
+ */
+#[wasm_bindgen]
+pub fn validateTransformKey(transform_key_obj: &JsValue) -> Array {
+    util::validate_transform_key(transform_key_obj, "transformKey").to_js_array()
+}
+
+/**
+ * Check that the provided encrypted value object is well-formed without attempting to decrypt it. Returns an array of
+ * diagnostics, one for every problem found, which is empty if the encrypted value is valid. Signatures are not verified.
  */
 #[wasm_bindgen]
-pub fn subtractPrivateKeys(private_key_a: &[u8], private_key_b: &[u8]) -> Result<Vec<u8>, JsError> {
-    let pubKeyA = PrivateKey::new(util::slice_to_fixed_32_bytes(private_key_a, "privateKeyA"));
-    let pubKeyB = PrivateKey::new(util::slice_to_fixed_32_bytes(private_key_b, "privateKeyB"));
-    Ok(pubKeyA.augment_minus(&pubKeyB).bytes().to_vec())
+pub fn validateEncryptedValue(encrypted_value_obj: &JsValue) -> Array {
+    util::validate_encrypted_value(encrypted_value_obj, "encryptedValue").to_js_array()
 }
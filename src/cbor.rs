@@ -0,0 +1,528 @@
+//! CBOR encodings of Recrypt values and a COSE_Encrypt (RFC 9052) envelope for data encrypted with a recrypt protected DEK.
+//!
+//! Values are encoded as CBOR maps with the same text keys as the properties of the JS object form, and every byte field
+//! as a CBOR byte string. Decoding converts the CBOR back into the JS object form and runs it through the same checks as
+//! any other value provided from JS.
+//!
+//! The COSE_Encrypt envelope is tag 96 wrapping `[protected, unprotected, ciphertext, [recipient]]`:
+//!
+//! - protected: `{1: 3}`, the content is encrypted with AES-256-GCM.
+//! - unprotected: `{5: iv}`, the 12 byte AES-GCM IV.
+//! - ciphertext: the AES-GCM ciphertext and tag, produced by the caller with the DEK and the AAD from coseEncryptAad.
+//! - recipient: `[protected, unprotected, encrypted DEK]`. The protected header is `{1: -65537}`, a value from the COSE
+//!   private use range which identifies a recrypt EncryptedValue. The unprotected header holds the value's
+//!   `"publicSigningKey"` and `"signature"`, and the encrypted DEK is the CBOR encoding of the rest of the EncryptedValue.
+use crate::util::{
+    self, BindingError, JsEncryptedValue, JsError, JsPublicKey, JsSigningKeyPair, JsTransformBlock,
+};
+use ciborium::Value;
+use js_sys::Array;
+use recrypt::api::{EncryptedValue, PublicKey, SigningKeypair, TransformKey};
+use wasm_bindgen::JsValue;
+use zeroize::{Zeroize, Zeroizing};
+
+const COSE_ENCRYPT_TAG: u64 = 96;
+const COSE_HEADER_ALG: i64 = 1;
+const COSE_HEADER_IV: i64 = 5;
+const COSE_ALG_A256GCM: i64 = 3;
+const COSE_ALG_RECRYPT: i64 = -65537;
+const AES_GCM_IV_SIZE: usize = 12;
+
+type MapEntries = Vec<(&'static str, Value)>;
+
+fn invalid_encoding(field: &str, message: String) -> BindingError {
+    BindingError::InvalidEncoding {
+        field: field.to_string(),
+        message,
+    }
+}
+
+fn text_map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::Text(key.to_string()), value))
+            .collect(),
+    )
+}
+
+fn int_map(entries: Vec<(i64, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(label, value)| (Value::Integer(label.into()), value))
+            .collect(),
+    )
+}
+
+fn public_key_to_cbor(public_key: JsPublicKey) -> Value {
+    text_map(vec![
+        ("x", Value::Bytes(public_key.x)),
+        ("y", Value::Bytes(public_key.y)),
+    ])
+}
+
+fn transform_block_to_cbor(block: JsTransformBlock) -> Value {
+    text_map(vec![
+        ("publicKey", public_key_to_cbor(block.publicKey)),
+        ("encryptedTempKey", Value::Bytes(block.encryptedTempKey)),
+        (
+            "randomTransformPublicKey",
+            public_key_to_cbor(block.randomTransformPublicKey),
+        ),
+        (
+            "randomTransformEncryptedTempKey",
+            Value::Bytes(block.randomTransformEncryptedTempKey),
+        ),
+    ])
+}
+
+/**
+ * Split an encrypted value into the map entries for its encrypted content and the entries for its signature, which the
+ * COSE envelope stores in a header instead.
+ */
+fn encrypted_value_entries(encrypted_value: JsEncryptedValue) -> (MapEntries, MapEntries) {
+    let content = vec![
+        (
+            "ephemeralPublicKey",
+            public_key_to_cbor(encrypted_value.ephemeralPublicKey),
+        ),
+        (
+            "encryptedMessage",
+            Value::Bytes(encrypted_value.encryptedMessage),
+        ),
+        ("authHash", Value::Bytes(encrypted_value.authHash)),
+        (
+            "transformBlocks",
+            Value::Array(
+                encrypted_value
+                    .transformBlocks
+                    .into_iter()
+                    .map(transform_block_to_cbor)
+                    .collect(),
+            ),
+        ),
+    ];
+    let signature = vec![
+        (
+            "publicSigningKey",
+            Value::Bytes(encrypted_value.publicSigningKey),
+        ),
+        ("signature", Value::Bytes(encrypted_value.signature)),
+    ];
+    (content, signature)
+}
+
+fn write<W: std::io::Write>(value: &Value, writer: W, field: &str) -> Result<(), BindingError> {
+    ciborium::into_writer(value, writer).map_err(|e| {
+        invalid_encoding(
+            field,
+            format!("Value for '{}' could not be encoded as CBOR: {}", field, e),
+        )
+    })
+}
+
+fn to_bytes(value: &Value, field: &str) -> Result<Vec<u8>, BindingError> {
+    let mut bytes = vec![];
+    write(value, &mut bytes, field)?;
+    Ok(bytes)
+}
+
+/**
+ * Same as to_bytes, but for a value holding a private key. The encoding is written into a buffer allocated at its final
+ * size, so no partial copies are left behind by reallocation, and the byte strings of the value are zeroed afterwards.
+ */
+fn to_secret_bytes(mut value: Value, field: &str) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+    let mut counter = util::ByteCounter::default();
+    let result = write(&value, &mut counter, field).and_then(|_| {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(counter.0));
+        write(&value, &mut *bytes, field).map(|_| bytes)
+    });
+    zeroize_bytes(&mut value);
+    result
+}
+
+/**
+ * Zero every byte string in the provided CBOR value, so that any secret it holds is cleared before it is freed. Arrays and
+ * maps are emptied and their whole buffers zeroed as well, as moving a Value copies its padding, which can hold stale
+ * bytes from the stack.
+ */
+fn zeroize_bytes(value: &mut Value) {
+    match value {
+        Value::Bytes(bytes) => bytes.zeroize(),
+        Value::Array(items) => {
+            items.iter_mut().for_each(zeroize_bytes);
+            items.clear();
+            items.spare_capacity_mut().zeroize();
+        }
+        Value::Map(entries) => {
+            entries
+                .iter_mut()
+                .for_each(|(_, value)| zeroize_bytes(value));
+            entries.clear();
+            entries.spare_capacity_mut().zeroize();
+        }
+        Value::Tag(_, value) => zeroize_bytes(value),
+        _ => (),
+    }
+}
+
+/**
+ * Decode exactly one CBOR item from the provided bytes, rejecting any trailing bytes.
+ */
+fn from_bytes(bytes: &[u8], field: &str) -> Result<Value, BindingError> {
+    let mut reader = bytes;
+    let value: Value = ciborium::from_reader(&mut reader).map_err(|e| {
+        invalid_encoding(
+            field,
+            format!("CBOR value in '{}' could not be decoded: {}", field, e),
+        )
+    })?;
+    if !reader.is_empty() {
+        return Err(invalid_encoding(
+            field,
+            format!(
+                "CBOR value in '{}' has {} unexpected trailing bytes.",
+                field,
+                reader.len()
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+/**
+ * Convert a decoded CBOR item into the equivalent JS value: maps with text keys into objects, byte strings into
+ * Uint8Arrays, and arrays, text, numbers, booleans and null into their JS counterparts. Tags and non-text map keys are
+ * rejected. The field is the path to the item and is extended for each nested item.
+ */
+fn cbor_to_js(value: &Value, field: &str) -> Result<JsValue, BindingError> {
+    match value {
+        Value::Map(entries) => {
+            let properties = entries
+                .iter()
+                .map(|(key, value)| match key {
+                    Value::Text(key) => {
+                        let value = cbor_to_js(value, &format!("{}.{}", field, key))?;
+                        Ok((key.as_str(), value))
+                    }
+                    _ => Err(invalid_encoding(
+                        field,
+                        format!("CBOR map in '{}' has a key which is not text.", field),
+                    )),
+                })
+                .collect::<Result<Vec<_>, BindingError>>()?;
+            Ok(util::new_js_object(&properties))
+        }
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| cbor_to_js(item, &format!("{}[{}]", field, index)))
+            .collect::<Result<Array, BindingError>>()
+            .map(JsValue::from),
+        Value::Bytes(bytes) => Ok(util::bytes_to_js(bytes)),
+        Value::Text(text) => Ok(JsValue::from_str(text)),
+        Value::Integer(integer) => Ok(JsValue::from_f64(i128::from(*integer) as f64)),
+        Value::Float(float) => Ok(JsValue::from_f64(*float)),
+        Value::Bool(boolean) => Ok(JsValue::from_bool(*boolean)),
+        Value::Null => Ok(JsValue::NULL),
+        _ => Err(invalid_encoding(
+            field,
+            format!("CBOR value in '{}' has an unsupported type.", field),
+        )),
+    }
+}
+
+fn decode_to_js(bytes: &[u8], field: &str) -> Result<JsValue, BindingError> {
+    cbor_to_js(&from_bytes(bytes, field)?, field)
+}
+
+/**
+ * Encode the provided PublicKey as a CBOR map with x/y byte strings.
+ */
+pub fn encode_public_key(public_key: PublicKey) -> Result<Vec<u8>, BindingError> {
+    to_bytes(
+        &public_key_to_cbor(util::public_key_to_js_object(public_key)),
+        "publicKey",
+    )
+}
+
+/**
+ * Decode and validate a PublicKey from CBOR.
+ */
+pub fn decode_public_key(bytes: &[u8], field: &str) -> Result<PublicKey, JsError> {
+    util::js_object_to_public_key(&decode_to_js(bytes, field)?, field)
+}
+
+/**
+ * Encode the provided EncryptedValue as a CBOR map with the same keys as the JS object form.
+ */
+pub fn encode_encrypted_value(encrypted_value: EncryptedValue) -> Result<Vec<u8>, BindingError> {
+    let (mut entries, signature) =
+        encrypted_value_entries(util::encrypted_value_to_js_object(encrypted_value));
+    entries.extend(signature);
+    to_bytes(&text_map(entries), "encryptedValue")
+}
+
+/**
+ * Decode and validate an EncryptedValue from CBOR.
+ */
+pub fn decode_encrypted_value(bytes: &[u8], field: &str) -> Result<EncryptedValue, JsError> {
+    util::js_object_to_encrypted_value(&decode_to_js(bytes, field)?, field)
+}
+
+/**
+ * Encode the provided TransformKey as a CBOR map with the same keys as the JS object form.
+ */
+pub fn encode_transform_key(transform_key: TransformKey) -> Result<Vec<u8>, BindingError> {
+    let transform_key = util::transform_key_to_js_object(transform_key);
+    to_bytes(
+        &text_map(vec![
+            (
+                "ephemeralPublicKey",
+                public_key_to_cbor(transform_key.ephemeralPublicKey),
+            ),
+            ("toPublicKey", public_key_to_cbor(transform_key.toPublicKey)),
+            (
+                "encryptedTempKey",
+                Value::Bytes(transform_key.encryptedTempKey),
+            ),
+            ("hashedTempKey", Value::Bytes(transform_key.hashedTempKey)),
+            (
+                "publicSigningKey",
+                Value::Bytes(transform_key.publicSigningKey),
+            ),
+            ("signature", Value::Bytes(transform_key.signature)),
+        ]),
+        "transformKey",
+    )
+}
+
+/**
+ * Decode and validate a TransformKey from CBOR.
+ */
+pub fn decode_transform_key(bytes: &[u8], field: &str) -> Result<TransformKey, JsError> {
+    util::js_object_to_transform_key(&decode_to_js(bytes, field)?, field)
+}
+
+/**
+ * Encode the provided SigningKeypair as a CBOR map with privateKey/publicKey byte strings.
+ */
+pub fn encode_signing_key_pair(
+    signing_key_pair: SigningKeypair,
+) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+    let JsSigningKeyPair {
+        privateKey,
+        publicKey,
+    } = util::signing_keys_to_js_object(signing_key_pair);
+    to_secret_bytes(
+        text_map(vec![
+            ("privateKey", Value::Bytes(privateKey.to_vec())),
+            ("publicKey", Value::Bytes(publicKey)),
+        ]),
+        "signingKeyPair",
+    )
+}
+
+/**
+ * Decode a SigningKeypair from CBOR, verifying that the public key matches the private key.
+ */
+pub fn decode_signing_key_pair(bytes: &[u8], field: &str) -> Result<SigningKeypair, JsError> {
+    let mut value = from_bytes(bytes, field)?;
+    let signing_key_pair = cbor_to_js(&value, field);
+    zeroize_bytes(&mut value);
+    util::js_object_to_signing_keypair(&signing_key_pair?, field)
+}
+
+fn content_protected_header() -> Result<Vec<u8>, BindingError> {
+    to_bytes(
+        &int_map(vec![(
+            COSE_HEADER_ALG,
+            Value::Integer(COSE_ALG_A256GCM.into()),
+        )]),
+        "protected",
+    )
+}
+
+/**
+ * The additional authenticated data the content of a COSE_Encrypt envelope must be encrypted with: the CBOR encoding of
+ * the Enc_structure `["Encrypt", protected, h'']`.
+ */
+pub fn cose_encrypt_aad() -> Result<Vec<u8>, BindingError> {
+    to_bytes(
+        &Value::Array(vec![
+            Value::Text("Encrypt".to_string()),
+            Value::Bytes(content_protected_header()?),
+            Value::Bytes(vec![]),
+        ]),
+        "aad",
+    )
+}
+
+pub struct CoseEncrypt {
+    pub encrypted_dek: EncryptedValue,
+    pub ciphertext: Vec<u8>,
+    pub iv: [u8; AES_GCM_IV_SIZE],
+}
+
+impl From<CoseEncrypt> for JsValue {
+    fn from(envelope: CoseEncrypt) -> JsValue {
+        util::new_js_object(&[
+            (
+                "encryptedDek",
+                util::encrypted_value_to_js_object(envelope.encrypted_dek).into(),
+            ),
+            ("ciphertext", util::bytes_to_js(&envelope.ciphertext)),
+            ("iv", util::bytes_to_js(&envelope.iv)),
+        ])
+    }
+}
+
+/**
+ * Wrap the provided AES-256-GCM ciphertext and the recrypt encrypted DEK it was encrypted with into a COSE_Encrypt
+ * envelope as described above.
+ */
+pub fn encode_cose_encrypt(envelope: CoseEncrypt) -> Result<Vec<u8>, BindingError> {
+    let (dek_entries, signature_entries) =
+        encrypted_value_entries(util::encrypted_value_to_js_object(envelope.encrypted_dek));
+    let recipient = Value::Array(vec![
+        Value::Bytes(to_bytes(
+            &int_map(vec![(
+                COSE_HEADER_ALG,
+                Value::Integer(COSE_ALG_RECRYPT.into()),
+            )]),
+            "recipient",
+        )?),
+        text_map(signature_entries),
+        Value::Bytes(to_bytes(&text_map(dek_entries), "encryptedDek")?),
+    ]);
+    to_bytes(
+        &Value::Tag(
+            COSE_ENCRYPT_TAG,
+            Box::new(Value::Array(vec![
+                Value::Bytes(content_protected_header()?),
+                int_map(vec![(COSE_HEADER_IV, Value::Bytes(envelope.iv.to_vec()))]),
+                Value::Bytes(envelope.ciphertext),
+                Value::Array(vec![recipient]),
+            ])),
+        ),
+        "envelope",
+    )
+}
+
+fn expect_array<const N: usize>(value: Value, field: &str) -> Result<[Value; N], BindingError> {
+    let error = || {
+        invalid_encoding(
+            field,
+            format!("Expected '{}' to be a CBOR array of {} items.", field, N),
+        )
+    };
+    match value {
+        Value::Array(items) => items.try_into().map_err(|_| error()),
+        _ => Err(error()),
+    }
+}
+
+fn expect_bytes(value: Value, field: &str) -> Result<Vec<u8>, BindingError> {
+    match value {
+        Value::Bytes(bytes) => Ok(bytes),
+        _ => Err(invalid_encoding(
+            field,
+            format!("Expected '{}' to be a CBOR byte string.", field),
+        )),
+    }
+}
+
+fn expect_map(value: Value, field: &str) -> Result<Vec<(Value, Value)>, BindingError> {
+    match value {
+        Value::Map(entries) => Ok(entries),
+        _ => Err(invalid_encoding(
+            field,
+            format!("Expected '{}' to be a CBOR map.", field),
+        )),
+    }
+}
+
+/**
+ * Remove and return the header parameter with the provided integer label.
+ */
+fn take_header(entries: &mut Vec<(Value, Value)>, label: i64) -> Option<Value> {
+    let index = entries
+        .iter()
+        .position(|(key, _)| key.as_integer() == Some(label.into()))?;
+    Some(entries.remove(index).1)
+}
+
+/**
+ * Check that the provided protected header is a serialized map with the expected algorithm.
+ */
+fn check_protected_alg(protected: Value, alg: i64, field: &str) -> Result<(), BindingError> {
+    let mut header = expect_map(from_bytes(&expect_bytes(protected, field)?, field)?, field)?;
+    match take_header(&mut header, COSE_HEADER_ALG) {
+        Some(value) if value.as_integer() == Some(alg.into()) => Ok(()),
+        _ => Err(invalid_encoding(
+            field,
+            format!(
+                "Expected the protected header '{}' to have alg {}.",
+                field, alg
+            ),
+        )),
+    }
+}
+
+/**
+ * Decode a COSE_Encrypt envelope produced by encode_cose_encrypt. The encrypted DEK gets the same validation as an
+ * encrypted value object passed to decrypt.
+ */
+pub fn decode_cose_encrypt(bytes: &[u8], field: &str) -> Result<CoseEncrypt, JsError> {
+    let content = match from_bytes(bytes, field)? {
+        Value::Tag(COSE_ENCRYPT_TAG, content) => *content,
+        _ => {
+            return Err(invalid_encoding(
+                field,
+                format!("Expected '{}' to be a COSE_Encrypt (tag 96).", field),
+            )
+            .into());
+        }
+    };
+    let [protected, unprotected, ciphertext, recipients] = expect_array(content, field)?;
+
+    check_protected_alg(protected, COSE_ALG_A256GCM, &format!("{}.protected", field))?;
+    let iv_field = format!("{}.unprotected.iv", field);
+    let mut unprotected = expect_map(unprotected, &format!("{}.unprotected", field))?;
+    let iv = take_header(&mut unprotected, COSE_HEADER_IV)
+        .ok_or_else(|| BindingError::MissingField(iv_field.clone()))
+        .and_then(|iv| expect_bytes(iv, &iv_field))
+        .and_then(|iv| util::slice_to_fixed_12_bytes(&iv, &iv_field))?;
+    let ciphertext = expect_bytes(ciphertext, &format!("{}.ciphertext", field))?;
+
+    let recipient_field = format!("{}.recipients[0]", field);
+    let [recipient] = expect_array(recipients, &format!("{}.recipients", field))?;
+    let [recipient_protected, recipient_unprotected, encrypted_dek] =
+        expect_array(recipient, &recipient_field)?;
+    check_protected_alg(
+        recipient_protected,
+        COSE_ALG_RECRYPT,
+        &format!("{}.protected", recipient_field),
+    )?;
+    let signature_entries = expect_map(
+        recipient_unprotected,
+        &format!("{}.unprotected", recipient_field),
+    )?;
+    let dek_field = format!("{}.ciphertext", recipient_field);
+    let mut dek_entries = expect_map(
+        from_bytes(&expect_bytes(encrypted_dek, &dek_field)?, &dek_field)?,
+        &dek_field,
+    )?;
+    dek_entries.extend(signature_entries);
+
+    let encrypted_dek = util::js_object_to_encrypted_value(
+        &cbor_to_js(&Value::Map(dek_entries), &recipient_field)?,
+        &recipient_field,
+    )?;
+    Ok(CoseEncrypt {
+        encrypted_dek,
+        ciphertext,
+        iv,
+    })
+}
@@ -0,0 +1,84 @@
+//! Deterministic derivation of recrypt private keys and Ed25519 seeds from a master seed, so that many key pairs (per
+//! device, per tenant, per purpose) can be recovered from one backed up secret.
+//!
+//! Keys are derived with HKDF-SHA256 (RFC 5869) in a single extract and expand:
+//!
+//! ```text
+//! prk  = HKDF-Extract(salt = "recrypt-wasm-binding key derivation v1", ikm = masterSeed)
+//! okm  = HKDF-Expand(prk, info, 32)
+//! info = label || 0x00 || for each path segment: (length of segment as a 4 byte big-endian integer) || segment
+//! ```
+//!
+//! The label separates the two kinds of key, so the same path gives unrelated recrypt and Ed25519 keys:
+//!
+//! - `"recrypt private key"` for deriveKeyPair. The 32 byte okm is used as-is as the recrypt private key.
+//! - `"ed25519 seed"` for deriveSigningKeyPair. The 32 byte okm is the Ed25519 seed, expanded as in RFC 8032.
+//!
+//! The salt and labels are ASCII and the segments are UTF-8, with no normalization. A path is one or more non-empty
+//! segments separated by `/`, such as `"tenant/acme/device/laptop"`. Length prefixing the segments means no two paths
+//! give the same info, and knowing a derived key reveals nothing about the master seed or keys on other paths.
+//!
+//! Master seeds must be at least 32 bytes and should be uniformly random, such as 32 bytes from crypto.getRandomValues.
+//! Other implementations rely on this derivation to recover the same keys, so it must never change. A new scheme would
+//! use a new salt.
+use crate::util::{BindingError, JsError};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+const SALT: &[u8] = b"recrypt-wasm-binding key derivation v1";
+const MIN_MASTER_SEED_SIZE: usize = 32;
+pub const RECRYPT_PRIVATE_KEY_LABEL: &[u8] = b"recrypt private key";
+pub const ED25519_SEED_LABEL: &[u8] = b"ed25519 seed";
+
+/**
+ * Build the HKDF info for the provided label and path, failing if the path is empty or has an empty segment.
+ */
+fn info(label: &[u8], path: &str, field: &str) -> Result<Vec<u8>, BindingError> {
+    let mut info = label.to_vec();
+    info.push(0);
+    for segment in path.split('/') {
+        if segment.is_empty() {
+            return Err(BindingError::InvalidEncoding {
+                field: field.to_string(),
+                message: format!(
+                    "Value for '{}' must be one or more non-empty segments separated by '/' but was '{}'.",
+                    field, path
+                ),
+            });
+        }
+        info.extend_from_slice(&(segment.len() as u32).to_be_bytes());
+        info.extend_from_slice(segment.as_bytes());
+    }
+    Ok(info)
+}
+
+/**
+ * Derive the 32 byte key with the provided label at the provided path from the master seed, as described above.
+ */
+pub fn derive_key(
+    master_seed: &[u8],
+    label: &[u8],
+    path: &str,
+    master_seed_field: &str,
+    path_field: &str,
+) -> Result<Zeroizing<[u8; 32]>, JsError> {
+    if master_seed.len() < MIN_MASTER_SEED_SIZE {
+        return Err(BindingError::InputWrongSize {
+            field: master_seed_field.to_string(),
+            expected: MIN_MASTER_SEED_SIZE,
+            actual: master_seed.len(),
+        }
+        .into());
+    }
+    let info = info(label, path, path_field)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    // 32 bytes is well under the 255 * 32 byte limit of HKDF-SHA256, so expand can't fail
+    Hkdf::<Sha256>::new(Some(SALT), master_seed)
+        .expand(&info, key.as_mut_slice())
+        .map_err(|_| BindingError::InvalidEncoding {
+            field: path_field.to_string(),
+            message: "Failed to derive a key.".to_string(),
+        })?;
+    Ok(key)
+}
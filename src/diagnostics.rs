@@ -0,0 +1,145 @@
+use crate::handles::{PrivateKeyHandle, SigningKeyHandle};
+use crate::util::JsError;
+use crate::values::{WasmEncryptedValue, WasmPublicKey, WasmTransformKey};
+use js_sys::{Array, ArrayBuffer, Reflect};
+#[cfg(feature = "console-diagnostics")]
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{JsCast, JsValue};
+
+#[cfg(feature = "console-diagnostics")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(message: &str);
+}
+
+/**
+ * Forward the message and location of any panic to `console.error`. Without this a panic only surfaces in JS as
+ * `RuntimeError: unreachable`. Runs automatically when the WASM module is instantiated.
+ */
+#[cfg(feature = "console-diagnostics")]
+#[wasm_bindgen(start)]
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error(&format!("recrypt-wasm-binding {}", info))
+    }));
+}
+
+/**
+ * Describes an input to a failed call for logging. Implementations must only ever describe the shape and size of a value
+ * and never its contents, since inputs include private keys and plaintexts.
+ */
+pub trait DescribeInput {
+    #[cfg_attr(not(feature = "console-diagnostics"), allow(dead_code))]
+    fn describe(&self) -> String;
+}
+
+impl DescribeInput for JsValue {
+    fn describe(&self) -> String {
+        let byte_length = || {
+            Reflect::get(self, &JsValue::from_str("byteLength"))
+                .ok()
+                .and_then(|length| length.as_f64())
+                .unwrap_or_default()
+        };
+        if self.is_undefined() {
+            "undefined".to_string()
+        } else if self.is_null() {
+            "null".to_string()
+        } else if self.is_instance_of::<ArrayBuffer>() || ArrayBuffer::is_view(self) {
+            format!("{} bytes", byte_length())
+        } else if let Some(array) = self.dyn_ref::<Array>() {
+            format!("array of {} values", array.length())
+        } else if self.is_object() {
+            "object".to_string()
+        } else {
+            self.js_typeof().as_string().unwrap_or_default()
+        }
+    }
+}
+
+impl DescribeInput for &str {
+    fn describe(&self) -> String {
+        format!("string of {} characters", self.chars().count())
+    }
+}
+
+impl<T: DescribeInput> DescribeInput for Option<T> {
+    fn describe(&self) -> String {
+        self.as_ref()
+            .map_or("undefined".to_string(), DescribeInput::describe)
+    }
+}
+
+impl DescribeInput for String {
+    fn describe(&self) -> String {
+        self.as_str().describe()
+    }
+}
+
+impl DescribeInput for WasmPublicKey {
+    fn describe(&self) -> String {
+        "WasmPublicKey".to_string()
+    }
+}
+
+impl DescribeInput for WasmTransformKey {
+    fn describe(&self) -> String {
+        "WasmTransformKey".to_string()
+    }
+}
+
+impl DescribeInput for WasmEncryptedValue {
+    fn describe(&self) -> String {
+        "WasmEncryptedValue".to_string()
+    }
+}
+
+impl DescribeInput for PrivateKeyHandle {
+    fn describe(&self) -> String {
+        "PrivateKeyHandle".to_string()
+    }
+}
+
+impl DescribeInput for SigningKeyHandle {
+    fn describe(&self) -> String {
+        "SigningKeyHandle".to_string()
+    }
+}
+
+/**
+ * Run the provided call and, when the `console-diagnostics` feature is enabled, log the operation name, the error code,
+ * field and message, and a description of each input to `console.error` if it fails. Without the feature this just runs
+ * the call.
+ */
+pub fn with_failure_context<T, F: FnOnce() -> Result<T, JsError>>(
+    operation: &str,
+    inputs: &[(&str, &dyn DescribeInput)],
+    call: F,
+) -> Result<T, JsError> {
+    let result = call();
+    #[cfg(feature = "console-diagnostics")]
+    if let Err(error) = &result {
+        let error_property = |name: &str| {
+            Reflect::get(error, &JsValue::from_str(name))
+                .ok()
+                .and_then(|value| value.as_string())
+                .unwrap_or_default()
+        };
+        let inputs: Vec<String> = inputs
+            .iter()
+            .map(|(name, input)| format!("{}: {}", name, input.describe()))
+            .collect();
+        console_error(&format!(
+            "recrypt-wasm-binding {} failed with {} (field '{}'): {} Inputs: {}",
+            operation,
+            error_property("code"),
+            error_property("field"),
+            error_property("message"),
+            inputs.join(", ")
+        ));
+    }
+    #[cfg(not(feature = "console-diagnostics"))]
+    let _ = (operation, inputs);
+    result
+}
@@ -0,0 +1,400 @@
+//! Compact binary encodings of Recrypt values for storage and transport.
+//!
+//! Every encoded value starts with a two byte header: the format version (currently 1) followed by a byte which
+//! identifies the kind of value that follows. All multi-byte integers are big-endian and every fixed size field is
+//! written using the same byte representation as the corresponding Uint8Array property of the JS object form. Public
+//! keys are written as their 32 byte x coordinate followed by their 32 byte y coordinate.
+//!
+//! Version 1 encrypted value (kind 1):
+//!
+//! | Field                                       | Size          |
+//! |---------------------------------------------|---------------|
+//! | ephemeralPublicKey                          | 64            |
+//! | encryptedMessage                            | 384           |
+//! | authHash                                    | 32            |
+//! | publicSigningKey                            | 32            |
+//! | signature                                   | 64            |
+//! | transform block count, 0 if not transformed | 4             |
+//! | transform blocks                            | 896 per block |
+//!
+//! Each transform block is its publicKey (64), encryptedTempKey (384), randomTransformPublicKey (64) and
+//! randomTransformEncryptedTempKey (384).
+//!
+//! Version 1 transform key (kind 2), 738 bytes in total:
+//!
+//! | Field              | Size |
+//! |--------------------|------|
+//! | ephemeralPublicKey | 64   |
+//! | toPublicKey        | 64   |
+//! | encryptedTempKey   | 384  |
+//! | hashedTempKey      | 128  |
+//! | publicSigningKey   | 32   |
+//! | signature          | 64   |
+//!
+//! Kind 3 is a private key wrapped with a password, described in src/password.rs.
+//!
+//! Compressed public keys are 33 bytes: a prefix of 2 if the y coordinate is even or 3 if it is odd, followed by the 32
+//! byte x coordinate. This matches the SEC1 compressed point form. Unlike the other encodings they have no version header,
+//! so they can be used anywhere a public key object is accepted.
+//!
+//! Decoding rejects values which are truncated, have trailing bytes, have an unknown
+//! version or kind, or contain a public key which isn't on the curve.
+use crate::util::{BindingError, Diagnostic, JsError, WasmError};
+use gridiron::fp_256::Fp256;
+use num_traits::Pow;
+use recrypt::{
+    api::{
+        AuthHash, Ed25519Signature, EncryptedMessage, EncryptedTempKey, EncryptedValue,
+        HashedValue, PublicKey, PublicSigningKey, TransformBlock, TransformKey,
+    },
+    nonemptyvec::NonEmptyVec,
+};
+
+pub const FORMAT_VERSION: u8 = 1;
+const ENCRYPTED_VALUE_KIND: u8 = 1;
+const TRANSFORM_KEY_KIND: u8 = 2;
+pub const PASSWORD_WRAPPED_KEY_KIND: u8 = 3;
+const PUBLIC_KEY_SIZE: usize = 64;
+const TEMP_KEY_SIZE: usize = 384;
+const TRANSFORM_BLOCK_SIZE: usize = 2 * PUBLIC_KEY_SIZE + 2 * TEMP_KEY_SIZE;
+const TRANSFORM_KEY_SIZE: usize = 2 + 2 * PUBLIC_KEY_SIZE + TEMP_KEY_SIZE + 128 + 32 + 64;
+// Size of an encoded value which has not been transformed, including the header and transform block count
+const ENCRYPTED_ONCE_VALUE_SIZE: usize = 2 + PUBLIC_KEY_SIZE + TEMP_KEY_SIZE + 32 + 32 + 64 + 4;
+
+const COMPRESSED_PUBLIC_KEY_SIZE: usize = 33;
+const COMPRESSED_EVEN_Y_PREFIX: u8 = 2;
+const COMPRESSED_ODD_Y_PREFIX: u8 = 3;
+// (p + 1) / 4 for the prime of the recrypt curve. Since p = 3 mod 4, a^((p + 1) / 4) is a square root of a whenever one
+// exists.
+#[rustfmt::skip]
+const SQRT_EXPONENT: [u8; 32] = [
+    35, 237, 64, 120, 210, 168, 225, 254, 106, 155, 251, 46, 24, 97, 55, 8,
+    123, 150, 226, 52, 72, 45, 109, 103, 134, 23, 43, 27, 23, 130, 37, 154,
+];
+
+pub fn write_public_key(out: &mut Vec<u8>, public_key: &PublicKey) {
+    let (x, y) = public_key.bytes_x_y();
+    out.extend_from_slice(x);
+    out.extend_from_slice(y);
+}
+
+/**
+ * Reads fixed size fields off the front of an encoded value, failing with an error that points at the field being read
+ * if the value is too short.
+ */
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    field: &'a str,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8], field: &'a str) -> ByteReader<'a> {
+        ByteReader {
+            bytes,
+            offset: 0,
+            field,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    pub fn error(&self, name: &str, message: String) -> BindingError {
+        BindingError::InvalidEncoding {
+            field: format!("{}.{}", self.field, name),
+            message,
+        }
+    }
+
+    pub fn read<const N: usize>(&mut self, name: &str) -> Result<[u8; N], BindingError> {
+        if self.remaining() < N {
+            return Err(self.error(
+                name,
+                format!(
+                    "Encoded value in '{}' ended before '{}'. Expected {} bytes at offset {} but only {} remain.",
+                    self.field,
+                    name,
+                    N,
+                    self.offset,
+                    self.remaining()
+                ),
+            ));
+        }
+        let mut fixed = [0u8; N];
+        fixed.copy_from_slice(&self.bytes[self.offset..self.offset + N]);
+        self.offset += N;
+        Ok(fixed)
+    }
+
+    pub fn read_u32(&mut self, name: &str) -> Result<u32, BindingError> {
+        self.read::<4>(name).map(u32::from_be_bytes)
+    }
+
+    /**
+     * Read the version and kind header, failing if either is not the one expected.
+     */
+    pub fn read_header(&mut self, kind: u8, kind_name: &str) -> Result<(), BindingError> {
+        let [version, actual_kind] = self.read::<2>("version")?;
+        if version != FORMAT_VERSION {
+            return Err(self.error(
+                "version",
+                format!(
+                    "Encoded value in '{}' has unsupported format version {}. Expected version {}.",
+                    self.field, version, FORMAT_VERSION
+                ),
+            ));
+        }
+        if actual_kind != kind {
+            return Err(self.error(
+                "kind",
+                format!(
+                    "Encoded value in '{}' has kind {} but expected kind {} ({}).",
+                    self.field, actual_kind, kind, kind_name
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn read_public_key(&mut self, name: &str) -> Result<PublicKey, JsError> {
+        let x = self.read::<32>(&format!("{}.x", name))?;
+        let y = self.read::<32>(&format!("{}.y", name))?;
+        PublicKey::new((x, y))
+            .map_err(|e| WasmError::with_field(e, &format!("{}.{}", self.field, name)).into())
+    }
+
+    /**
+     * Fail if there are any bytes left over after the entire value has been read.
+     */
+    pub fn finish(self) -> Result<(), BindingError> {
+        if self.remaining() != 0 {
+            return Err(BindingError::InvalidEncoding {
+                field: self.field.to_string(),
+                message: format!(
+                    "Encoded value in '{}' has {} unexpected trailing bytes after offset {}.",
+                    self.field,
+                    self.remaining(),
+                    self.offset
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Encode the provided EncryptedValue into the version 1 binary format described above.
+ */
+pub fn encode_encrypted_value(encrypted_value: &EncryptedValue) -> Vec<u8> {
+    let (EncryptedValue::EncryptedOnceValue {
+        ephemeral_public_key,
+        encrypted_message,
+        auth_hash,
+        public_signing_key,
+        signature,
+    }
+    | EncryptedValue::TransformedValue {
+        ephemeral_public_key,
+        encrypted_message,
+        auth_hash,
+        public_signing_key,
+        signature,
+        ..
+    }) = encrypted_value;
+    let transform_blocks = match encrypted_value {
+        EncryptedValue::EncryptedOnceValue { .. } => vec![],
+        EncryptedValue::TransformedValue {
+            transform_blocks, ..
+        } => transform_blocks.to_vec(),
+    };
+
+    let mut out = Vec::with_capacity(
+        ENCRYPTED_ONCE_VALUE_SIZE + transform_blocks.len() * TRANSFORM_BLOCK_SIZE,
+    );
+    out.extend_from_slice(&[FORMAT_VERSION, ENCRYPTED_VALUE_KIND]);
+    write_public_key(&mut out, ephemeral_public_key);
+    out.extend_from_slice(encrypted_message.bytes());
+    out.extend_from_slice(auth_hash.bytes());
+    out.extend_from_slice(public_signing_key.bytes());
+    out.extend_from_slice(signature.bytes());
+    out.extend_from_slice(&(transform_blocks.len() as u32).to_be_bytes());
+    for block in transform_blocks.iter() {
+        write_public_key(&mut out, block.public_key());
+        out.extend_from_slice(block.encrypted_temp_key().bytes());
+        write_public_key(&mut out, block.random_transform_public_key());
+        out.extend_from_slice(block.encrypted_random_transform_temp_key().bytes());
+    }
+    out
+}
+
+/**
+ * Decode an EncryptedValue from the binary format written by encode_encrypted_value.
+ */
+pub fn decode_encrypted_value(bytes: &[u8], field: &str) -> Result<EncryptedValue, JsError> {
+    let mut reader = ByteReader::new(bytes, field);
+    reader.read_header(ENCRYPTED_VALUE_KIND, "encrypted value")?;
+    let ephemeral_public_key = reader.read_public_key("ephemeralPublicKey")?;
+    let encrypted_message = EncryptedMessage::new(reader.read("encryptedMessage")?);
+    let auth_hash = AuthHash::new(reader.read("authHash")?);
+    let public_signing_key = PublicSigningKey::new(reader.read("publicSigningKey")?);
+    let signature = Ed25519Signature::new(reader.read("signature")?);
+    let block_count = reader.read_u32("transformBlocks")? as usize;
+    // Check the declared count against the remaining length before reading so a corrupt count can't cause a huge allocation
+    if block_count.checked_mul(TRANSFORM_BLOCK_SIZE) != Some(reader.remaining()) {
+        return Err(reader
+            .error(
+                "transformBlocks",
+                format!(
+                    "Encoded value in '{}' declares {} transform blocks which need {} bytes, but {} bytes remain.",
+                    field,
+                    block_count,
+                    block_count.saturating_mul(TRANSFORM_BLOCK_SIZE),
+                    reader.remaining()
+                ),
+            )
+            .into());
+    }
+
+    let mut transform_blocks = Vec::with_capacity(block_count);
+    for index in 0..block_count {
+        let block_field = format!("transformBlocks[{}]", index);
+        let public_key = reader.read_public_key(&format!("{}.publicKey", block_field))?;
+        let encrypted_temp_key = reader.read(&format!("{}.encryptedTempKey", block_field))?;
+        let random_transform_public_key =
+            reader.read_public_key(&format!("{}.randomTransformPublicKey", block_field))?;
+        let random_transform_encrypted_temp_key =
+            reader.read(&format!("{}.randomTransformEncryptedTempKey", block_field))?;
+        transform_blocks.push(
+            TransformBlock::new(
+                &public_key,
+                &EncryptedTempKey::new(encrypted_temp_key),
+                &random_transform_public_key,
+                &EncryptedTempKey::new(random_transform_encrypted_temp_key),
+            )
+            .map_err(|e| WasmError::with_field(e, &format!("{}.{}", field, block_field)))?,
+        );
+    }
+    reader.finish()?;
+
+    if transform_blocks.is_empty() {
+        Ok(EncryptedValue::EncryptedOnceValue {
+            ephemeral_public_key,
+            encrypted_message,
+            auth_hash,
+            public_signing_key,
+            signature,
+        })
+    } else {
+        Ok(EncryptedValue::TransformedValue {
+            ephemeral_public_key,
+            encrypted_message,
+            auth_hash,
+            public_signing_key,
+            signature,
+            transform_blocks: NonEmptyVec::try_from(&transform_blocks).map_err(WasmError::new)?,
+        })
+    }
+}
+
+/**
+ * Encode the provided TransformKey into the version 1 binary format described above. Augmented transform keys are encoded
+ * the same way as any other transform key.
+ */
+pub fn encode_transform_key(transform_key: &TransformKey) -> Vec<u8> {
+    let mut out = Vec::with_capacity(TRANSFORM_KEY_SIZE);
+    out.extend_from_slice(&[FORMAT_VERSION, TRANSFORM_KEY_KIND]);
+    write_public_key(&mut out, transform_key.ephemeral_public_key());
+    write_public_key(&mut out, transform_key.to_public_key());
+    out.extend_from_slice(transform_key.encrypted_temp_key().bytes());
+    out.extend_from_slice(transform_key.hashed_temp_key().bytes());
+    out.extend_from_slice(transform_key.public_signing_key().bytes());
+    out.extend_from_slice(transform_key.signature().bytes());
+    out
+}
+
+/**
+ * Decode a TransformKey from the binary format written by encode_transform_key.
+ */
+pub fn decode_transform_key(bytes: &[u8], field: &str) -> Result<TransformKey, JsError> {
+    let mut reader = ByteReader::new(bytes, field);
+    reader.read_header(TRANSFORM_KEY_KIND, "transform key")?;
+    let ephemeral_public_key = reader.read_public_key("ephemeralPublicKey")?;
+    let to_public_key = reader.read_public_key("toPublicKey")?;
+    let encrypted_temp_key = EncryptedTempKey::new(reader.read("encryptedTempKey")?);
+    let hashed_temp_key = HashedValue::new(reader.read("hashedTempKey")?)
+        .map_err(|e| WasmError::with_field(e, &format!("{}.hashedTempKey", field)))?;
+    let public_signing_key = PublicSigningKey::new(reader.read("publicSigningKey")?);
+    let signature = Ed25519Signature::new(reader.read("signature")?);
+    reader.finish()?;
+
+    Ok(TransformKey::new(
+        ephemeral_public_key,
+        to_public_key,
+        encrypted_temp_key,
+        hashed_temp_key,
+        public_signing_key,
+        signature,
+    ))
+}
+
+/**
+ * Compress the provided public key into the 33 byte form described above.
+ */
+pub fn compress_public_key(public_key: &PublicKey) -> [u8; COMPRESSED_PUBLIC_KEY_SIZE] {
+    let (x, y) = public_key.bytes_x_y();
+    let mut compressed = [0u8; COMPRESSED_PUBLIC_KEY_SIZE];
+    compressed[0] = if y[31] & 1 == 0 {
+        COMPRESSED_EVEN_Y_PREFIX
+    } else {
+        COMPRESSED_ODD_Y_PREFIX
+    };
+    compressed[1..].copy_from_slice(x);
+    compressed
+}
+
+/**
+ * Decompress a 33 byte compressed public key by solving y^2 = x^3 + 3 for y and picking the root with the parity recorded
+ * in the prefix. The resulting point goes through the same validation as any other public key.
+ */
+pub fn decompress_public_key(
+    compressed: &[u8; COMPRESSED_PUBLIC_KEY_SIZE],
+    field: &str,
+) -> Result<PublicKey, Diagnostic> {
+    let invalid_key = |message: &str| BindingError::InvalidPublicKey {
+        field: field.to_string(),
+        message: message.to_string(),
+    };
+    let prefix = compressed[0];
+    if prefix != COMPRESSED_EVEN_Y_PREFIX && prefix != COMPRESSED_ODD_Y_PREFIX {
+        return Err(BindingError::InvalidEncoding {
+            field: field.to_string(),
+            message: format!(
+                "Compressed public key in '{}' has prefix {} but expected {} or {}.",
+                field, prefix, COMPRESSED_EVEN_Y_PREFIX, COMPRESSED_ODD_Y_PREFIX
+            ),
+        }
+        .into());
+    }
+    let mut x_bytes = [0u8; 32];
+    x_bytes.copy_from_slice(&compressed[1..]);
+    let x = Fp256::from(x_bytes);
+    // Converting bytes to a field element silently reduces them, so reject x coordinates which aren't fully reduced
+    if x.to_bytes_array() != x_bytes {
+        return Err(invalid_key(
+            "The x coordinate of the compressed public key is not a field element.",
+        )
+        .into());
+    }
+    let y_squared = x * x * x + Fp256::from(3u8);
+    let mut y = y_squared.pow(Fp256::from(SQRT_EXPONENT));
+    if y * y != y_squared {
+        return Err(invalid_key("The compressed public key is not a point on the curve.").into());
+    }
+    if y.to_bytes_array()[31] & 1 != prefix & 1 {
+        y = -y;
+    }
+    PublicKey::new((x_bytes, y.to_bytes_array()))
+        .map_err(|e| WasmError::with_field(e, field).into())
+}
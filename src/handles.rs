@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+use crate::util;
+use recrypt::api::{PrivateKey, PublicKey, SigningKeypair};
+use wasm_bindgen::prelude::*;
+
+/**
+ * A Recrypt private key held in WASM memory. The key bytes are never returned to JS: handles are created by
+ * Api256.generateKeyPairHandle, importPrivateKey, unwrapPrivateKey or unwrapPrivateKeyHandleWithPassword and can only
+ * be exported by wrapping them with wrapPrivateKey or wrapPrivateKeyWithPassword. Calling `free()` zeroes the key. The
+ * matching public key is computed when the handle is created.
+ */
+#[wasm_bindgen]
+pub struct PrivateKeyHandle {
+    // Boxed so the key stays in one place. wasm-bindgen moves the handle itself out of its allocation when it is freed,
+    // which would leave the original copy behind. recrypt clears PrivateKey in place when it is dropped, through
+    // clear_on_drop, whose writes the compiler can't remove, so the boxed key is the only copy and needs no clearing here.
+    pub(crate) key: Box<PrivateKey>,
+    pub(crate) public_key: PublicKey,
+}
+
+#[wasm_bindgen]
+impl PrivateKeyHandle {
+    /**
+     * The public key for this private key, as a public key object in x/y form.
+     */
+    #[wasm_bindgen(getter)]
+    pub fn publicKey(&self) -> JsValue {
+        util::public_key_to_js_object(self.public_key).into()
+    }
+}
+
+/**
+ * An Ed25519 signing key pair held in WASM memory, already parsed and checked. Created by Api256.generateSigningKeyHandle,
+ * importSigningKey or unwrapPrivateKeyHandleWithPassword and accepted by every operation which signs. Only the public key
+ * can be read back out. Calling `free()` zeroes the key pair.
+ */
+#[wasm_bindgen]
+pub struct SigningKeyHandle {
+    // Boxed, and cleared by recrypt when dropped, the same as PrivateKeyHandle.key
+    pub(crate) key_pair: Box<SigningKeypair>,
+}
+
+#[wasm_bindgen]
+impl SigningKeyHandle {
+    /**
+     * The 32 byte Ed25519 public key of this key pair.
+     */
+    #[wasm_bindgen(getter)]
+    pub fn publicKey(&self) -> Vec<u8> {
+        self.key_pair.public_key().bytes().to_vec()
+    }
+}
@@ -0,0 +1,210 @@
+//! Identity bundles: a recrypt key pair and an Ed25519 signing key pair serialized together with their metadata as one
+//! versioned JSON object, for backing up a user's keys or moving them between devices.
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "createdAt": <milliseconds since the Unix epoch>,
+//!   "keyId": <JWK thumbprint of the recrypt public key>,
+//!   "signingKeyId": <JWK thumbprint of the Ed25519 public key>,
+//!   "keyPair": {"privateKey", "publicKey": {"x", "y"}},
+//!   "signingKeyPair": {"privateKey", "publicKey"},
+//!   "checksum": <SHA-256 of the canonical bundle without its checksum>
+//! }
+//! ```
+//!
+//! Byte fields use the same unpadded base64url encoding as src/json.rs. Key IDs are RFC 7638 thumbprints of the public
+//! keys' JWKs (see src/jwk.rs), so they match what a JWK library computes for the exported keys.
+//!
+//! The checksum is taken over the compact serialization with sorted keys, which is how bundles are written. It catches
+//! truncation and accidental edits, but it is not a MAC: anyone able to change the bundle can recompute it. Importing
+//! also recomputes both public keys from their private keys and both key IDs from the public keys, so a bundle that
+//! imports always holds consistent keys.
+use crate::json::{self, JsonObject};
+use crate::jwk;
+use crate::util::{self, BindingError, JsError};
+use recrypt::api::{PrivateKey, PublicKey, SigningKeypair};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+const IDENTITY_FORMAT_VERSION: u64 = 1;
+
+/**
+ * A recrypt key pair and Ed25519 signing key pair belonging to one user, with the time they were created.
+ */
+pub struct Identity {
+    pub private_key: PrivateKey,
+    pub public_key: PublicKey,
+    pub signing_key_pair: SigningKeypair,
+    pub created_at: u64,
+}
+
+fn checksum(bundle: &Value) -> String {
+    util::bytes_to_base64url(Sha256::digest(json::to_secret_json(bundle).as_bytes()))
+}
+
+/**
+ * Check that the provided public key is the one computed from the private key.
+ */
+fn check_key_pair<F>(
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+    compute_public_key: F,
+    field: &str,
+) -> Result<(), JsError>
+where
+    F: FnOnce(&PrivateKey) -> Result<PublicKey, JsError>,
+{
+    if &compute_public_key(private_key)? == public_key {
+        Ok(())
+    } else {
+        let public_key_field = format!("{}.publicKey", field);
+        Err(BindingError::InvalidPublicKey {
+            message: format!(
+                "Value for '{}' is not the public key of the private key in '{}.privateKey'.",
+                public_key_field, field
+            ),
+            field: public_key_field,
+        }
+        .into())
+    }
+}
+
+fn check_key_id(object: &mut JsonObject, name: &str, expected: &str) -> Result<(), BindingError> {
+    let key_id = object.take_string(name)?;
+    if key_id == expected {
+        Ok(())
+    } else {
+        let field = object.path(name);
+        Err(BindingError::InvalidEncoding {
+            message: format!(
+                "Value for '{}' does not match the thumbprint of its public key.",
+                field
+            ),
+            field,
+        })
+    }
+}
+
+impl Identity {
+    /**
+     * Serialize the identity as a checksummed bundle. The recrypt key pair is checked with compute_public_key first so
+     * that a mismatched pair can't be exported. The key pair field is used in any error.
+     */
+    pub fn to_json<F>(
+        &self,
+        compute_public_key: F,
+        key_pair_field: &str,
+    ) -> Result<Zeroizing<String>, JsError>
+    where
+        F: FnOnce(&PrivateKey) -> Result<PublicKey, JsError>,
+    {
+        check_key_pair(
+            &self.private_key,
+            &self.public_key,
+            compute_public_key,
+            key_pair_field,
+        )?;
+        let signing_public_key = self.signing_key_pair.public_key();
+        let mut bundle = json::to_json_object(vec![
+            ("version", IDENTITY_FORMAT_VERSION.into()),
+            ("createdAt", self.created_at.into()),
+            ("keyId", jwk::public_key_thumbprint(&self.public_key).into()),
+            (
+                "signingKeyId",
+                jwk::signing_public_key_thumbprint(signing_public_key.bytes()).into(),
+            ),
+            (
+                "keyPair",
+                json::key_pair_to_value(self.private_key.clone(), self.public_key),
+            ),
+            (
+                "signingKeyPair",
+                json::signing_key_pair_to_value(self.signing_key_pair.clone()),
+            ),
+        ]);
+        let checksum = checksum(&bundle);
+        if let Value::Object(fields) = &mut bundle {
+            fields.insert("checksum".to_string(), checksum.into());
+        }
+        let json = json::to_secret_json(&bundle);
+        json::zeroize_strings(&mut bundle);
+        Ok(json)
+    }
+
+    /**
+     * Parse a bundle produced by to_json. The checksum is verified before anything else, then the recrypt public key is
+     * recomputed with compute_public_key, the signing key pair is checked the same way as computeEd25519PublicKey and the
+     * key IDs are recomputed.
+     */
+    pub fn from_json<F>(json: &str, compute_public_key: F, field: &str) -> Result<Identity, JsError>
+    where
+        F: FnOnce(&PrivateKey) -> Result<PublicKey, JsError>,
+    {
+        let mut bundle = json::parse_json(json, field)?;
+        let checksum_field = format!("{}.checksum", field);
+        let provided_checksum = match &mut bundle {
+            Value::Object(fields) => fields.remove("checksum"),
+            _ => None,
+        };
+        let checked = match provided_checksum {
+            Some(Value::String(provided)) if provided == checksum(&bundle) => Ok(()),
+            Some(Value::String(_)) => Err(BindingError::InvalidEncoding {
+                message: format!(
+                    "Value for '{}' does not match the contents of '{}'. The bundle has been corrupted or modified.",
+                    checksum_field, field
+                ),
+                field: checksum_field,
+            }),
+            Some(_) => Err(BindingError::InvalidType {
+                field: checksum_field,
+                expected: "a base64url string",
+            }),
+            // Also reached for a bundle which isn't an object, which JsonObject reports below
+            None if !bundle.is_object() => Ok(()),
+            None => Err(BindingError::MissingField(checksum_field)),
+        };
+        // The bundle still holds both private keys, which JsonObject would otherwise clear
+        if let Err(e) = checked {
+            json::zeroize_strings(&mut bundle);
+            return Err(e.into());
+        }
+
+        let mut object = JsonObject::versioned(bundle, field, IDENTITY_FORMAT_VERSION)?;
+        let created_at = object.take_u64("createdAt")?;
+        let key_pair_field = object.path("keyPair");
+        let key_pair = object.take_key_pair("keyPair")?;
+        let signing_key_pair_field = object.path("signingKeyPair");
+        let signing_key_pair = object.take_signing_key_pair("signingKeyPair")?;
+
+        let (private_key, public_key) =
+            util::js_object_to_key_pair(&key_pair.into(), &key_pair_field)?;
+        check_key_pair(
+            &private_key,
+            &public_key,
+            compute_public_key,
+            &key_pair_field,
+        )?;
+        let signing_key_pair =
+            util::js_object_to_signing_keypair(&signing_key_pair.into(), &signing_key_pair_field)?;
+
+        check_key_id(
+            &mut object,
+            "keyId",
+            &jwk::public_key_thumbprint(&public_key),
+        )?;
+        check_key_id(
+            &mut object,
+            "signingKeyId",
+            &jwk::signing_public_key_thumbprint(signing_key_pair.public_key().bytes()),
+        )?;
+        object.finish()?;
+        Ok(Identity {
+            private_key,
+            public_key,
+            signing_key_pair,
+            created_at,
+        })
+    }
+}
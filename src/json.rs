@@ -0,0 +1,503 @@
+//! JSON encodings of Recrypt values for REST APIs.
+//!
+//! Each value is a JSON object with the same keys as the JS object form, where every byte field is an unpadded base64url
+//! string (RFC 4648 section 5) and every public key is a nested `{"x", "y"}` object. The top level object also has a
+//! `"version"` field, currently 1. Parsing is strict: unknown fields, missing fields, padded or non-url-safe base64 and
+//! other versions are all rejected. Parsed values then go through the same checks as the JS object form.
+use crate::util::{
+    self, BindingError, JsEncryptedValue, JsError, JsKeyPair, JsPublicKey, JsSigningKeyPair,
+    JsTransformBlock, JsTransformKey,
+};
+use recrypt::api::{EncryptedValue, PrivateKey, PublicKey, SigningKeypair, TransformKey};
+use serde_json::{Map, Value};
+use wasm_bindgen::JsValue;
+use zeroize::{Zeroize, Zeroizing};
+
+const JSON_FORMAT_VERSION: u64 = 1;
+
+fn bytes_to_json(bytes: &[u8]) -> Value {
+    Value::String(util::bytes_to_base64url(bytes))
+}
+
+fn public_key_object_to_json(public_key: &JsPublicKey) -> Value {
+    let mut object = Map::new();
+    object.insert("x".to_string(), bytes_to_json(&public_key.x));
+    object.insert("y".to_string(), bytes_to_json(&public_key.y));
+    Value::Object(object)
+}
+
+/**
+ * Build a JSON object from the provided fields.
+ */
+pub fn to_json_object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+}
+
+/**
+ * Build the top level JSON object with the version field and the provided fields and serialize it.
+ */
+fn to_versioned_json(mut fields: Vec<(&str, Value)>) -> String {
+    fields.push(("version", JSON_FORMAT_VERSION.into()));
+    to_json_object(fields).to_string()
+}
+
+/**
+ * Same as to_versioned_json, but for fields holding a private key. The strings of the fields are zeroed once written.
+ */
+fn to_versioned_secret_json(mut fields: Vec<(&str, Value)>) -> Zeroizing<String> {
+    fields.push(("version", JSON_FORMAT_VERSION.into()));
+    let mut object = to_json_object(fields);
+    let json = to_secret_json(&object);
+    zeroize_strings(&mut object);
+    json
+}
+
+/**
+ * Serialize a JSON value holding a secret, such as the base64url of a private key. The value is written into a buffer
+ * allocated at its final size, so no partial copies are left behind by reallocation.
+ */
+pub fn to_secret_json(value: &Value) -> Zeroizing<String> {
+    let mut counter = util::ByteCounter::default();
+    // Writing a Value into memory can't fail
+    let _ = serde_json::to_writer(&mut counter, value);
+    let mut bytes = Zeroizing::new(Vec::with_capacity(counter.0));
+    let _ = serde_json::to_writer(&mut *bytes, value);
+    // serde_json always writes UTF-8
+    Zeroizing::new(String::from_utf8(std::mem::take(&mut *bytes)).unwrap_or_default())
+}
+
+/**
+ * Zero every string in the provided JSON value, so that the base64url of any secret it holds is cleared before it is
+ * freed.
+ */
+pub fn zeroize_strings(value: &mut Value) {
+    match value {
+        Value::String(string) => string.zeroize(),
+        Value::Array(values) => values.iter_mut().for_each(zeroize_strings),
+        Value::Object(object) => object.values_mut().for_each(zeroize_strings),
+        _ => (),
+    }
+}
+
+/**
+ * Parse a JSON string without checking its contents.
+ */
+pub fn parse_json(json: &str, field: &str) -> Result<Value, BindingError> {
+    serde_json::from_str(json).map_err(|e| BindingError::InvalidEncoding {
+        field: field.to_string(),
+        message: format!("JSON value in '{}' could not be parsed: {}", field, e),
+    })
+}
+
+/**
+ * A JSON object being parsed. Every expected field must be taken exactly once, and any field left over when the object is
+ * finished is reported as unknown. The strings of any fields which weren't taken are zeroed when it is dropped, so a
+ * private key is cleared even when parsing fails before it is taken.
+ */
+pub struct JsonObject {
+    fields: Map<String, Value>,
+    field: String,
+}
+
+impl JsonObject {
+    pub fn new(mut value: Value, field: &str) -> Result<JsonObject, BindingError> {
+        match value {
+            Value::Object(fields) => Ok(JsonObject {
+                fields,
+                field: field.to_string(),
+            }),
+            _ => {
+                zeroize_strings(&mut value);
+                Err(BindingError::InvalidType {
+                    field: field.to_string(),
+                    expected: "a JSON object",
+                })
+            }
+        }
+    }
+
+    /**
+     * Parse a top level JSON string, checking its version field.
+     */
+    fn parse_versioned(json: &str, field: &str) -> Result<JsonObject, BindingError> {
+        JsonObject::versioned(parse_json(json, field)?, field, JSON_FORMAT_VERSION)
+    }
+
+    /**
+     * Start parsing a top level JSON object, checking that its version field is the expected version.
+     */
+    pub fn versioned(value: Value, field: &str, expected: u64) -> Result<JsonObject, BindingError> {
+        let mut object = JsonObject::new(value, field)?;
+        let version_field = format!("{}.version", field);
+        match object.take("version")? {
+            Value::Number(version) if version.as_u64() == Some(expected) => Ok(object),
+            _ => Err(BindingError::InvalidEncoding {
+                field: version_field.clone(),
+                message: format!(
+                    "Value for '{}' is not a supported version. Expected {}.",
+                    version_field, expected
+                ),
+            }),
+        }
+    }
+
+    pub fn path(&self, name: &str) -> String {
+        format!("{}.{}", self.field, name)
+    }
+
+    pub fn take(&mut self, name: &str) -> Result<Value, BindingError> {
+        self.fields
+            .remove(name)
+            .ok_or_else(|| BindingError::MissingField(self.path(name)))
+    }
+
+    pub fn take_bytes(&mut self, name: &str) -> Result<Vec<u8>, BindingError> {
+        let path = self.path(name);
+        match self.take(name)? {
+            Value::String(encoded) => util::base64url_to_bytes(&encoded, &path),
+            _ => Err(BindingError::InvalidType {
+                field: path,
+                expected: "a base64url string",
+            }),
+        }
+    }
+
+    /**
+     * Same as take_bytes, but for a secret. Both the base64url string and the decoded bytes are zeroed when dropped.
+     */
+    pub fn take_secret_bytes(&mut self, name: &str) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+        let path = self.path(name);
+        match self.take(name)? {
+            Value::String(encoded) => {
+                util::base64url_to_bytes(&Zeroizing::new(encoded), &path).map(Zeroizing::new)
+            }
+            _ => Err(BindingError::InvalidType {
+                field: path,
+                expected: "a base64url string",
+            }),
+        }
+    }
+
+    pub fn take_string(&mut self, name: &str) -> Result<String, BindingError> {
+        let path = self.path(name);
+        match self.take(name)? {
+            Value::String(value) => Ok(value),
+            _ => Err(BindingError::InvalidType {
+                field: path,
+                expected: "a string",
+            }),
+        }
+    }
+
+    pub fn take_u64(&mut self, name: &str) -> Result<u64, BindingError> {
+        let path = self.path(name);
+        self.take(name)?.as_u64().ok_or(BindingError::InvalidType {
+            field: path,
+            expected: "a non-negative integer",
+        })
+    }
+
+    pub fn take_object(&mut self, name: &str) -> Result<JsonObject, BindingError> {
+        let path = self.path(name);
+        JsonObject::new(self.take(name)?, &path)
+    }
+
+    fn take_public_key(&mut self, name: &str) -> Result<JsPublicKey, BindingError> {
+        let mut object = self.take_object(name)?;
+        let public_key = JsPublicKey {
+            x: object.take_bytes("x")?,
+            y: object.take_bytes("y")?,
+        };
+        object.finish()?;
+        Ok(public_key)
+    }
+
+    /**
+     * Take a nested recrypt key pair object. Only its shape is checked, not its contents.
+     */
+    pub fn take_key_pair(&mut self, name: &str) -> Result<JsKeyPair, BindingError> {
+        let mut object = self.take_object(name)?;
+        let key_pair = object.key_pair_fields()?;
+        object.finish()?;
+        Ok(key_pair)
+    }
+
+    /**
+     * Take a nested Ed25519 signing key pair object. Only its shape is checked, not its contents.
+     */
+    pub fn take_signing_key_pair(&mut self, name: &str) -> Result<JsSigningKeyPair, BindingError> {
+        let mut object = self.take_object(name)?;
+        let signing_key_pair = object.signing_key_pair_fields()?;
+        object.finish()?;
+        Ok(signing_key_pair)
+    }
+
+    fn key_pair_fields(&mut self) -> Result<JsKeyPair, BindingError> {
+        let private_key = util::slice_to_fixed_32_bytes(
+            &self.take_secret_bytes("privateKey")?,
+            &self.path("privateKey"),
+        )?;
+        Ok(JsKeyPair {
+            privateKey: Zeroizing::new(private_key),
+            publicKey: self.take_public_key("publicKey")?,
+        })
+    }
+
+    fn signing_key_pair_fields(&mut self) -> Result<JsSigningKeyPair, BindingError> {
+        Ok(JsSigningKeyPair {
+            privateKey: self.take_secret_bytes("privateKey")?,
+            publicKey: self.take_bytes("publicKey")?,
+        })
+    }
+
+    /**
+     * Fail if the object has any fields which weren't taken.
+     */
+    pub fn finish(self) -> Result<(), BindingError> {
+        match self.fields.keys().next() {
+            Some(unknown) => Err(BindingError::InvalidEncoding {
+                field: self.path(unknown),
+                message: format!("Unknown field '{}'.", self.path(unknown)),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for JsonObject {
+    fn drop(&mut self) {
+        self.fields.values_mut().for_each(zeroize_strings);
+    }
+}
+
+/**
+ * Serialize the provided PublicKey as versioned JSON.
+ */
+pub fn public_key_to_json(public_key: PublicKey) -> String {
+    let public_key = util::public_key_to_js_object(public_key);
+    to_versioned_json(vec![
+        ("x", bytes_to_json(&public_key.x)),
+        ("y", bytes_to_json(&public_key.y)),
+    ])
+}
+
+/**
+ * Parse and validate a PublicKey from versioned JSON.
+ */
+pub fn public_key_from_json(json: &str, field: &str) -> Result<PublicKey, JsError> {
+    let mut object = JsonObject::parse_versioned(json, field)?;
+    let public_key = JsPublicKey {
+        x: object.take_bytes("x")?,
+        y: object.take_bytes("y")?,
+    };
+    object.finish()?;
+    util::js_object_to_public_key(&public_key.into(), field)
+}
+
+/**
+ * Serialize the provided recrypt key pair as versioned JSON.
+ */
+pub fn key_pair_to_json(private_key: PrivateKey, public_key: PublicKey) -> Zeroizing<String> {
+    to_versioned_secret_json(key_pair_to_fields(private_key, public_key))
+}
+
+fn key_pair_to_fields(
+    private_key: PrivateKey,
+    public_key: PublicKey,
+) -> Vec<(&'static str, Value)> {
+    let key_pair = util::key_pair_to_js_object(private_key, public_key);
+    vec![
+        ("privateKey", bytes_to_json(key_pair.privateKey.as_slice())),
+        ("publicKey", public_key_object_to_json(&key_pair.publicKey)),
+    ]
+}
+
+/**
+ * Convert the provided recrypt key pair into an unversioned JSON object, for nesting inside another value.
+ */
+pub fn key_pair_to_value(private_key: PrivateKey, public_key: PublicKey) -> Value {
+    to_json_object(key_pair_to_fields(private_key, public_key))
+}
+
+/**
+ * Parse and validate a recrypt key pair from versioned JSON.
+ */
+pub fn key_pair_from_json(json: &str, field: &str) -> Result<(PrivateKey, PublicKey), JsError> {
+    let mut object = JsonObject::parse_versioned(json, field)?;
+    let key_pair = object.key_pair_fields()?;
+    object.finish()?;
+    util::js_object_to_key_pair(&key_pair.into(), field)
+}
+
+/**
+ * Serialize the provided Ed25519 signing key pair as versioned JSON.
+ */
+pub fn signing_key_pair_to_json(signing_key_pair: SigningKeypair) -> Zeroizing<String> {
+    to_versioned_secret_json(signing_key_pair_to_fields(signing_key_pair))
+}
+
+fn signing_key_pair_to_fields(signing_key_pair: SigningKeypair) -> Vec<(&'static str, Value)> {
+    let signing_key_pair = util::signing_keys_to_js_object(signing_key_pair);
+    vec![
+        ("privateKey", bytes_to_json(&signing_key_pair.privateKey)),
+        ("publicKey", bytes_to_json(&signing_key_pair.publicKey)),
+    ]
+}
+
+/**
+ * Convert the provided Ed25519 signing key pair into an unversioned JSON object, for nesting inside another value.
+ */
+pub fn signing_key_pair_to_value(signing_key_pair: SigningKeypair) -> Value {
+    to_json_object(signing_key_pair_to_fields(signing_key_pair))
+}
+
+/**
+ * Parse an Ed25519 signing key pair from versioned JSON, verifying that the public key matches the private key.
+ */
+pub fn signing_key_pair_from_json(json: &str, field: &str) -> Result<SigningKeypair, JsError> {
+    let mut object = JsonObject::parse_versioned(json, field)?;
+    let signing_key_pair = object.signing_key_pair_fields()?;
+    object.finish()?;
+    util::js_object_to_signing_keypair(&signing_key_pair.into(), field)
+}
+
+/**
+ * Serialize the provided TransformKey as versioned JSON.
+ */
+pub fn transform_key_to_json(transform_key: TransformKey) -> String {
+    let transform_key = util::transform_key_to_js_object(transform_key);
+    to_versioned_json(vec![
+        (
+            "ephemeralPublicKey",
+            public_key_object_to_json(&transform_key.ephemeralPublicKey),
+        ),
+        (
+            "toPublicKey",
+            public_key_object_to_json(&transform_key.toPublicKey),
+        ),
+        (
+            "encryptedTempKey",
+            bytes_to_json(&transform_key.encryptedTempKey),
+        ),
+        ("hashedTempKey", bytes_to_json(&transform_key.hashedTempKey)),
+        (
+            "publicSigningKey",
+            bytes_to_json(&transform_key.publicSigningKey),
+        ),
+        ("signature", bytes_to_json(&transform_key.signature)),
+    ])
+}
+
+/**
+ * Parse and validate a TransformKey from versioned JSON.
+ */
+pub fn transform_key_from_json(json: &str, field: &str) -> Result<TransformKey, JsError> {
+    let mut object = JsonObject::parse_versioned(json, field)?;
+    let transform_key = JsTransformKey {
+        ephemeralPublicKey: object.take_public_key("ephemeralPublicKey")?,
+        toPublicKey: object.take_public_key("toPublicKey")?,
+        encryptedTempKey: object.take_bytes("encryptedTempKey")?,
+        hashedTempKey: object.take_bytes("hashedTempKey")?,
+        publicSigningKey: object.take_bytes("publicSigningKey")?,
+        signature: object.take_bytes("signature")?,
+    };
+    object.finish()?;
+    util::js_object_to_transform_key(&transform_key.into(), field)
+}
+
+/**
+ * Serialize the provided EncryptedValue as versioned JSON. The transformBlocks array is empty for a value which has not
+ * been transformed.
+ */
+pub fn encrypted_value_to_json(encrypted_value: EncryptedValue) -> String {
+    let encrypted_value = util::encrypted_value_to_js_object(encrypted_value);
+    let transform_blocks = encrypted_value
+        .transformBlocks
+        .iter()
+        .map(|block| {
+            let mut object = Map::new();
+            object.insert(
+                "publicKey".to_string(),
+                public_key_object_to_json(&block.publicKey),
+            );
+            object.insert(
+                "encryptedTempKey".to_string(),
+                bytes_to_json(&block.encryptedTempKey),
+            );
+            object.insert(
+                "randomTransformPublicKey".to_string(),
+                public_key_object_to_json(&block.randomTransformPublicKey),
+            );
+            object.insert(
+                "randomTransformEncryptedTempKey".to_string(),
+                bytes_to_json(&block.randomTransformEncryptedTempKey),
+            );
+            Value::Object(object)
+        })
+        .collect();
+    to_versioned_json(vec![
+        (
+            "ephemeralPublicKey",
+            public_key_object_to_json(&encrypted_value.ephemeralPublicKey),
+        ),
+        (
+            "encryptedMessage",
+            bytes_to_json(&encrypted_value.encryptedMessage),
+        ),
+        ("authHash", bytes_to_json(&encrypted_value.authHash)),
+        (
+            "publicSigningKey",
+            bytes_to_json(&encrypted_value.publicSigningKey),
+        ),
+        ("signature", bytes_to_json(&encrypted_value.signature)),
+        ("transformBlocks", Value::Array(transform_blocks)),
+    ])
+}
+
+/**
+ * Parse and validate an EncryptedValue from versioned JSON.
+ */
+pub fn encrypted_value_from_json(json: &str, field: &str) -> Result<EncryptedValue, JsError> {
+    let mut object = JsonObject::parse_versioned(json, field)?;
+    let blocks_field = format!("{}.transformBlocks", field);
+    let transform_blocks = match object.take("transformBlocks")? {
+        Value::Array(blocks) => blocks
+            .into_iter()
+            .enumerate()
+            .map(|(index, block)| {
+                let mut block = JsonObject::new(block, &format!("{}[{}]", blocks_field, index))?;
+                let transform_block = JsTransformBlock {
+                    publicKey: block.take_public_key("publicKey")?,
+                    encryptedTempKey: block.take_bytes("encryptedTempKey")?,
+                    randomTransformPublicKey: block.take_public_key("randomTransformPublicKey")?,
+                    randomTransformEncryptedTempKey: block
+                        .take_bytes("randomTransformEncryptedTempKey")?,
+                };
+                block.finish()?;
+                Ok(transform_block)
+            })
+            .collect::<Result<Vec<_>, BindingError>>()?,
+        _ => {
+            return Err(BindingError::InvalidType {
+                field: blocks_field,
+                expected: "a JSON array",
+            }
+            .into());
+        }
+    };
+    let encrypted_value = JsEncryptedValue {
+        ephemeralPublicKey: object.take_public_key("ephemeralPublicKey")?,
+        encryptedMessage: object.take_bytes("encryptedMessage")?,
+        authHash: object.take_bytes("authHash")?,
+        publicSigningKey: object.take_bytes("publicSigningKey")?,
+        signature: object.take_bytes("signature")?,
+        transformBlocks: transform_blocks,
+    };
+    object.finish()?;
+    util::js_object_to_encrypted_value(&JsValue::from(encrypted_value), field)
+}
@@ -0,0 +1,385 @@
+//! JWE (RFC 7516) compact and JSON serializations of content encrypted with a recrypt protected content encryption key.
+//!
+//! The JOSE header is `{"alg":"RECRYPT","enc":"A256GCM"}`. `RECRYPT` is a private algorithm name, so only this binding
+//! can unwrap the key, but the rest of the JWE can be parsed by any standard JOSE library.
+//!
+//! - The CEK is `deriveSymmetricKey` of a fresh `generatePlaintext` value.
+//! - The JWE Encrypted Key is the versioned binary encoding (see src/encoding.rs) of the recrypt EncryptedValue of that
+//!   plaintext.
+//! - The content is encrypted with AES-256-GCM using a random 96 bit IV and `ASCII(BASE64URL(protected header))` as the
+//!   additional data, followed by `"." || aad` when the JSON serialization has an `aad` member.
+//!
+//! The Encrypted Key isn't covered by the additional data, so a JWE is transformed by replacing its Encrypted Key with
+//! the transformed EncryptedValue and leaving everything else untouched. Decryption handles both forms the same way.
+//!
+//! JSON serializations are produced in the general syntax with a single recipient. Parsing also accepts the flattened
+//! syntax, and merges the protected, shared unprotected and per-recipient headers before checking `alg` and `enc`. As
+//! RFC 7516 requires, a JWE which sets the same header parameter in more than one of them is rejected.
+use crate::encoding;
+use crate::util::{self, BindingError, JsError};
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce, Tag, aead::AeadInPlace};
+use recrypt::api::EncryptedValue;
+use serde_json::{Map, Value};
+use zeroize::Zeroizing;
+
+const JWE_ALG_RECRYPT: &str = "RECRYPT";
+const JWE_ENC_A256GCM: &str = "A256GCM";
+
+fn invalid_encoding(field: &str, message: String) -> BindingError {
+    BindingError::InvalidEncoding {
+        field: field.to_string(),
+        message,
+    }
+}
+
+/**
+ * How a parsed JWE was serialized, so it can be written back out the same way once its Encrypted Key is replaced. The
+ * JSON form keeps the original object so that any members this binding doesn't use are preserved.
+ */
+enum Serialization {
+    Compact,
+    Json(Map<String, Value>),
+}
+
+pub struct Jwe {
+    protected: String,
+    pub encrypted_key: EncryptedValue,
+    iv: [u8; 12],
+    ciphertext: Vec<u8>,
+    tag: [u8; 16],
+    aad: Option<String>,
+    serialization: Serialization,
+}
+
+fn protected_header() -> String {
+    let mut header = Map::new();
+    header.insert("alg".to_string(), JWE_ALG_RECRYPT.into());
+    header.insert("enc".to_string(), JWE_ENC_A256GCM.into());
+    util::bytes_to_base64url(Value::Object(header).to_string())
+}
+
+fn content_aad(protected: &str, aad: &Option<String>) -> String {
+    match aad {
+        Some(aad) => format!("{}.{}", protected, aad),
+        None => protected.to_string(),
+    }
+}
+
+impl Jwe {
+    /**
+     * Encrypt the message with the CEK and build a JWE around it. The encrypted key must be the recrypt encrypted value
+     * of the plaintext the CEK was derived from.
+     */
+    pub fn encrypt(
+        cek: &[u8; 32],
+        encrypted_key: EncryptedValue,
+        message: &[u8],
+        json: bool,
+    ) -> Result<Jwe, BindingError> {
+        let protected = protected_header();
+        let iv = util::random_bytes::<12>()?;
+        let mut ciphertext = message.to_vec();
+        let tag = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(cek))
+            .encrypt_in_place_detached(
+                Nonce::from_slice(&iv),
+                protected.as_bytes(),
+                &mut ciphertext,
+            )
+            .map_err(|_| {
+                invalid_encoding("message", "Message is too large to encrypt.".to_string())
+            })?;
+        let serialization = if json {
+            let mut object = Map::new();
+            object.insert("protected".to_string(), protected.clone().into());
+            object.insert(
+                "recipients".to_string(),
+                Value::Array(vec![Value::Object(Map::new())]),
+            );
+            object.insert("iv".to_string(), util::bytes_to_base64url(iv).into());
+            object.insert(
+                "ciphertext".to_string(),
+                util::bytes_to_base64url(&ciphertext).into(),
+            );
+            object.insert("tag".to_string(), util::bytes_to_base64url(tag).into());
+            Serialization::Json(object)
+        } else {
+            Serialization::Compact
+        };
+        Ok(Jwe {
+            protected,
+            encrypted_key,
+            iv,
+            ciphertext,
+            tag: tag.into(),
+            aad: None,
+            serialization,
+        })
+    }
+
+    /**
+     * Parse a JWE in either the compact or JSON serialization, checking that it uses the RECRYPT algorithm and A256GCM.
+     */
+    pub fn parse(jwe: &str, field: &str) -> Result<Jwe, JsError> {
+        if jwe.trim_start().starts_with('{') {
+            Jwe::parse_json(jwe, field)
+        } else {
+            Jwe::parse_compact(jwe, field)
+        }
+    }
+
+    fn parse_compact(jwe: &str, field: &str) -> Result<Jwe, JsError> {
+        let parts: Vec<&str> = jwe.trim().split('.').collect();
+        let [protected, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+            return Err(invalid_encoding(
+                field,
+                format!(
+                    "JWE in '{}' has {} parts. The compact serialization has 5.",
+                    field,
+                    parts.len()
+                ),
+            )
+            .into());
+        };
+        check_header(
+            &decode_header(protected, &format!("{}.protected", field))?,
+            field,
+        )?;
+        Ok(Jwe {
+            protected: protected.to_string(),
+            encrypted_key: decode_encrypted_key(
+                encrypted_key,
+                &format!("{}.encrypted_key", field),
+            )?,
+            iv: decode_iv(iv, &format!("{}.iv", field))?,
+            ciphertext: util::base64url_to_bytes(ciphertext, &format!("{}.ciphertext", field))?,
+            tag: decode_tag(tag, &format!("{}.tag", field))?,
+            aad: None,
+            serialization: Serialization::Compact,
+        })
+    }
+
+    fn parse_json(jwe: &str, field: &str) -> Result<Jwe, JsError> {
+        let object = match serde_json::from_str(jwe) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => {
+                return Err(BindingError::InvalidType {
+                    field: field.to_string(),
+                    expected: "a JSON object",
+                }
+                .into());
+            }
+            Err(e) => {
+                return Err(invalid_encoding(
+                    field,
+                    format!("JSON value in '{}' could not be parsed: {}", field, e),
+                )
+                .into());
+            }
+        };
+        // The flattened syntax has the recipient's members at the top level rather than in a recipients array
+        let (recipient, recipient_field) = match object.get("recipients") {
+            None => (&object, field.to_string()),
+            Some(Value::Array(recipients)) => match &recipients[..] {
+                [Value::Object(recipient)] => (recipient, format!("{}.recipients[0]", field)),
+                _ => {
+                    return Err(invalid_encoding(
+                        &format!("{}.recipients", field),
+                        format!(
+                            "Value for '{}.recipients' must have exactly one recipient object.",
+                            field
+                        ),
+                    )
+                    .into());
+                }
+            },
+            Some(_) => {
+                return Err(BindingError::InvalidType {
+                    field: format!("{}.recipients", field),
+                    expected: "a JSON array",
+                }
+                .into());
+            }
+        };
+
+        let protected = optional_string(&object, "protected", field)?.unwrap_or_default();
+        let mut header = if protected.is_empty() {
+            Map::new()
+        } else {
+            decode_header(&protected, &format!("{}.protected", field))?
+        };
+        for (source, name, source_field) in [
+            (&object, "unprotected", field),
+            (recipient, "header", recipient_field.as_str()),
+        ] {
+            match source.get(name) {
+                None => (),
+                Some(Value::Object(unprotected)) => {
+                    // RFC 7516 section 7.2.1 requires the header parameter names to be disjoint
+                    if let Some(duplicate) =
+                        unprotected.keys().find(|key| header.contains_key(*key))
+                    {
+                        let duplicate_field = format!("{}.{}.{}", source_field, name, duplicate);
+                        let message = format!(
+                            "Value for '{}' is already set in another header of the JWE.",
+                            duplicate_field
+                        );
+                        return Err(invalid_encoding(&duplicate_field, message).into());
+                    }
+                    header.extend(unprotected.clone())
+                }
+                Some(_) => {
+                    return Err(BindingError::InvalidType {
+                        field: format!("{}.{}", source_field, name),
+                        expected: "a JSON object",
+                    }
+                    .into());
+                }
+            }
+        }
+        check_header(&header, field)?;
+
+        let required = |source: &Map<String, Value>, name: &str, source_field: &str| {
+            optional_string(source, name, source_field)?
+                .ok_or_else(|| BindingError::MissingField(format!("{}.{}", source_field, name)))
+        };
+        let encrypted_key = decode_encrypted_key(
+            &required(recipient, "encrypted_key", &recipient_field)?,
+            &format!("{}.encrypted_key", recipient_field),
+        )?;
+        Ok(Jwe {
+            encrypted_key,
+            iv: decode_iv(&required(&object, "iv", field)?, &format!("{}.iv", field))?,
+            ciphertext: util::base64url_to_bytes(
+                &required(&object, "ciphertext", field)?,
+                &format!("{}.ciphertext", field),
+            )?,
+            tag: decode_tag(&required(&object, "tag", field)?, &format!("{}.tag", field))?,
+            aad: optional_string(&object, "aad", field)?,
+            protected,
+            serialization: Serialization::Json(object.clone()),
+        })
+    }
+
+    /**
+     * Decrypt the content with the CEK derived from the plaintext of the Encrypted Key.
+     */
+    pub fn decrypt(&self, cek: &[u8; 32], field: &str) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+        let mut message = Zeroizing::new(self.ciphertext.clone());
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(cek))
+            .decrypt_in_place_detached(
+                Nonce::from_slice(&self.iv),
+                content_aad(&self.protected, &self.aad).as_bytes(),
+                &mut message,
+                Tag::from_slice(&self.tag),
+            )
+            .map_err(|_| BindingError::DecryptFailed {
+                field: field.to_string(),
+                message: format!(
+                    "Content of the JWE in '{}' could not be decrypted. The JWE was modified or the private key is not the recipient's.",
+                    field
+                ),
+            })?;
+        Ok(message)
+    }
+
+    /**
+     * Write the JWE back out in the serialization it was created or parsed with.
+     */
+    pub fn serialize(&self) -> String {
+        let encrypted_key =
+            util::bytes_to_base64url(encoding::encode_encrypted_value(&self.encrypted_key));
+        match &self.serialization {
+            Serialization::Compact => [
+                self.protected.clone(),
+                encrypted_key,
+                util::bytes_to_base64url(self.iv),
+                util::bytes_to_base64url(&self.ciphertext),
+                util::bytes_to_base64url(self.tag),
+            ]
+            .join("."),
+            Serialization::Json(object) => {
+                let mut object = object.clone();
+                match object
+                    .get_mut("recipients")
+                    .and_then(Value::as_array_mut)
+                    .and_then(|recipients| recipients.first_mut())
+                    .and_then(Value::as_object_mut)
+                {
+                    Some(recipient) => {
+                        recipient.insert("encrypted_key".to_string(), encrypted_key.into())
+                    }
+                    None => object.insert("encrypted_key".to_string(), encrypted_key.into()),
+                };
+                Value::Object(object).to_string()
+            }
+        }
+    }
+}
+
+fn optional_string(
+    object: &Map<String, Value>,
+    name: &str,
+    field: &str,
+) -> Result<Option<String>, BindingError> {
+    match object.get(name) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(BindingError::InvalidType {
+            field: format!("{}.{}", field, name),
+            expected: "a base64url string",
+        }),
+    }
+}
+
+fn decode_header(protected: &str, field: &str) -> Result<Map<String, Value>, BindingError> {
+    match serde_json::from_slice(&util::base64url_to_bytes(protected, field)?) {
+        Ok(Value::Object(header)) => Ok(header),
+        _ => Err(invalid_encoding(
+            field,
+            format!(
+                "Value for '{}' is not a base64url encoded JSON object.",
+                field
+            ),
+        )),
+    }
+}
+
+/**
+ * Check that the JOSE header names the algorithms this binding implements. Compression and critical extensions aren't
+ * supported, so JWEs which use them are rejected rather than decrypted incorrectly.
+ */
+fn check_header(header: &Map<String, Value>, field: &str) -> Result<(), BindingError> {
+    for (name, expected) in [("alg", JWE_ALG_RECRYPT), ("enc", JWE_ENC_A256GCM)] {
+        if header.get(name).and_then(Value::as_str) != Some(expected) {
+            return Err(invalid_encoding(
+                &format!("{}.{}", field, name),
+                format!(
+                    "JWE in '{}' has an unsupported '{}' header. Expected '{}'.",
+                    field, name, expected
+                ),
+            ));
+        }
+    }
+    match ["zip", "crit"]
+        .iter()
+        .find(|name| header.contains_key(**name))
+    {
+        Some(name) => Err(invalid_encoding(
+            &format!("{}.{}", field, name),
+            format!("JWE in '{}' uses the unsupported '{}' header.", field, name),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn decode_encrypted_key(encoded: &str, field: &str) -> Result<EncryptedValue, JsError> {
+    encoding::decode_encrypted_value(&util::base64url_to_bytes(encoded, field)?, field)
+}
+
+fn decode_iv(encoded: &str, field: &str) -> Result<[u8; 12], BindingError> {
+    util::slice_to_fixed_12_bytes(&util::base64url_to_bytes(encoded, field)?, field)
+}
+
+fn decode_tag(encoded: &str, field: &str) -> Result<[u8; 16], BindingError> {
+    util::slice_to_fixed_16_bytes(&util::base64url_to_bytes(encoded, field)?, field)
+}
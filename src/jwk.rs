@@ -0,0 +1,265 @@
+//! JWK (RFC 7517) forms of recrypt and Ed25519 keys. Every byte member is unpadded base64url.
+//!
+//! Ed25519 signing key pairs use the standard OKP form from RFC 8037: `{"kty": "OKP", "crv": "Ed25519", "x", "d"}`,
+//! where `x` is the 32 byte public key and `d` is the 32 byte seed, the first half of the 64 byte private signing key.
+//!
+//! Recrypt keys use the private key type `{"kty": "RECRYPT", "crv": "Fp256", "x", "y"}`, where `x` and `y` are the 32 byte
+//! big-endian coordinates of the public key. Private keys add `d`, the 32 byte private key.
+//!
+//! Importing ignores members this binding doesn't use, such as `kid`, `use` or `key_ops`, as RFC 7517 requires.
+//!
+//! Thumbprints follow RFC 7638: the base64url SHA-256 of the required public members, sorted and without whitespace.
+use crate::json;
+use crate::util::{self, BindingError, JsError, JsPublicKey};
+use recrypt::api::{PrivateKey, PublicKey, SigningKeypair};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::JsValue;
+use zeroize::Zeroizing;
+
+const KTY_OKP: &str = "OKP";
+const CRV_ED25519: &str = "Ed25519";
+const KTY_RECRYPT: &str = "RECRYPT";
+const CRV_FP256: &str = "Fp256";
+
+/**
+ * The kinds of key a JWK can hold. Converted into the matching JS object form once imported.
+ */
+pub enum JwkKey {
+    PublicKey(PublicKey),
+    KeyPair(PrivateKey, PublicKey),
+    SigningKeyPair(SigningKeypair),
+}
+
+impl From<JwkKey> for JsValue {
+    fn from(key: JwkKey) -> JsValue {
+        match key {
+            JwkKey::PublicKey(public_key) => util::public_key_to_js_object(public_key).into(),
+            JwkKey::KeyPair(private_key, public_key) => {
+                util::key_pair_to_js_object(private_key, public_key).into()
+            }
+            JwkKey::SigningKeyPair(signing_key_pair) => {
+                util::signing_keys_to_js_object(signing_key_pair).into()
+            }
+        }
+    }
+}
+
+/**
+ * Read a key from its JS object form: an Ed25519 signing key pair if it has a 32 byte publicKey property, a recrypt key
+ * pair if it has any other publicKey property, and otherwise a recrypt public key.
+ */
+pub fn js_value_to_key(value: &JsValue, field: &str) -> Result<JwkKey, JsError> {
+    if !util::has_js_property(value, "publicKey") {
+        return Ok(JwkKey::PublicKey(util::js_object_to_public_key(
+            value, field,
+        )?));
+    }
+    let public_key = util::get_js_property(value, "publicKey", field)?;
+    if util::js_value_to_bytes(&public_key, field).is_ok_and(|bytes| bytes.len() == 32) {
+        Ok(JwkKey::SigningKeyPair(util::js_object_to_signing_keypair(
+            value, field,
+        )?))
+    } else {
+        let (private_key, public_key) = util::js_object_to_key_pair(value, field)?;
+        Ok(JwkKey::KeyPair(private_key, public_key))
+    }
+}
+
+fn recrypt_jwk(public_key: PublicKey, private_key: Option<PrivateKey>) -> JsValue {
+    let JsPublicKey { x, y } = util::public_key_to_js_object(public_key);
+    let mut members = vec![
+        ("kty", KTY_RECRYPT.into()),
+        ("crv", CRV_FP256.into()),
+        ("x", util::bytes_to_base64url(x).into()),
+        ("y", util::bytes_to_base64url(y).into()),
+    ];
+    if let Some(private_key) = private_key {
+        members.push((
+            "d",
+            Zeroizing::new(util::bytes_to_base64url(private_key.bytes()))
+                .as_str()
+                .into(),
+        ));
+    }
+    util::new_js_object(&members)
+}
+
+/**
+ * Convert the provided key into a JWK object.
+ */
+pub fn key_to_jwk(key: JwkKey) -> JsValue {
+    match key {
+        JwkKey::PublicKey(public_key) => recrypt_jwk(public_key, None),
+        JwkKey::KeyPair(private_key, public_key) => recrypt_jwk(public_key, Some(private_key)),
+        JwkKey::SigningKeyPair(signing_key_pair) => {
+            let signing_keys = util::signing_keys_to_js_object(signing_key_pair);
+            util::new_js_object(&[
+                ("kty", KTY_OKP.into()),
+                ("crv", CRV_ED25519.into()),
+                (
+                    "x",
+                    util::bytes_to_base64url(&signing_keys.publicKey).into(),
+                ),
+                (
+                    "d",
+                    Zeroizing::new(util::bytes_to_base64url(&signing_keys.privateKey[..32]))
+                        .as_str()
+                        .into(),
+                ),
+            ])
+        }
+    }
+}
+
+fn jwk_bytes(jwk: &JsValue, name: &str, field: &str) -> Result<Vec<u8>, BindingError> {
+    util::base64url_to_bytes(
+        &util::get_js_string_property(jwk, name, field)?,
+        &format!("{}.{}", field, name),
+    )
+}
+
+/**
+ * Same as jwk_bytes, but for the private key member. Both the base64url string and the decoded bytes are zeroed when
+ * dropped.
+ */
+fn jwk_private_bytes(jwk: &JsValue, field: &str) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+    util::base64url_to_bytes(
+        &Zeroizing::new(util::get_js_string_property(jwk, "d", field)?),
+        &format!("{}.d", field),
+    )
+    .map(Zeroizing::new)
+}
+
+fn expect_member(
+    jwk: &JsValue,
+    name: &str,
+    expected: &str,
+    field: &str,
+) -> Result<(), BindingError> {
+    let value = util::get_js_string_property(jwk, name, field)?;
+    if value == expected {
+        Ok(())
+    } else {
+        let member_field = format!("{}.{}", field, name);
+        Err(BindingError::InvalidEncoding {
+            message: format!(
+                "JWK '{}' of '{}' is not supported. Expected '{}'.",
+                member_field, value, expected
+            ),
+            field: member_field,
+        })
+    }
+}
+
+/**
+ * Read a key from a JWK object, validating it the same way as the JS object form. The public key of a recrypt private key
+ * is recomputed with compute_public_key and must match `x` and `y`.
+ */
+pub fn jwk_to_key<F>(jwk: &JsValue, compute_public_key: F, field: &str) -> Result<JwkKey, JsError>
+where
+    F: FnOnce(&PrivateKey) -> Result<PublicKey, JsError>,
+{
+    let kty = util::get_js_string_property(jwk, "kty", field)?;
+    match kty.as_str() {
+        KTY_OKP => {
+            expect_member(jwk, "crv", CRV_ED25519, field)?;
+            if !util::has_js_property(jwk, "d") {
+                return Err(BindingError::InvalidEncoding {
+                    field: format!("{}.d", field),
+                    message: format!(
+                        "Required value '{}.d' was not provided. Only Ed25519 key pairs can be imported, not public keys alone.",
+                        field
+                    ),
+                }
+                .into());
+            }
+            let x = util::slice_to_fixed_32_bytes(
+                &jwk_bytes(jwk, "x", field)?,
+                &format!("{}.x", field),
+            )?;
+            let d = util::slice_to_fixed_32_bytes(
+                &jwk_private_bytes(jwk, field)?,
+                &format!("{}.d", field),
+            )?;
+            let mut key_pair_bytes = Zeroizing::new([0u8; 64]);
+            key_pair_bytes[..32].copy_from_slice(&d);
+            key_pair_bytes[32..].copy_from_slice(&x);
+            // Fails if x is not the public key of d
+            Ok(JwkKey::SigningKeyPair(util::slice_to_signing_keypair(
+                key_pair_bytes.as_slice(),
+                &format!("{}.x", field),
+            )?))
+        }
+        KTY_RECRYPT => {
+            expect_member(jwk, "crv", CRV_FP256, field)?;
+            let public_key = util::js_object_to_public_key(
+                &JsPublicKey {
+                    x: jwk_bytes(jwk, "x", field)?,
+                    y: jwk_bytes(jwk, "y", field)?,
+                }
+                .into(),
+                field,
+            )?;
+            if util::has_js_property(jwk, "d") {
+                let d = util::slice_to_fixed_32_bytes(
+                    &jwk_private_bytes(jwk, field)?,
+                    &format!("{}.d", field),
+                )?;
+                let private_key = PrivateKey::new(d);
+                if compute_public_key(&private_key)? != public_key {
+                    let x_field = format!("{}.x", field);
+                    return Err(BindingError::InvalidPublicKey {
+                        message: format!(
+                            "Values for '{}' and '{}.y' are not the public key of '{}.d'.",
+                            x_field, field, field
+                        ),
+                        field: x_field,
+                    }
+                    .into());
+                }
+                Ok(JwkKey::KeyPair(private_key, public_key))
+            } else {
+                Ok(JwkKey::PublicKey(public_key))
+            }
+        }
+        _ => Err(BindingError::InvalidEncoding {
+            field: format!("{}.kty", field),
+            message: format!(
+                "JWK '{}.kty' of '{}' is not supported. Expected '{}' or '{}'.",
+                field, kty, KTY_OKP, KTY_RECRYPT
+            ),
+        }
+        .into()),
+    }
+}
+
+fn thumbprint(members: Vec<(&str, Value)>) -> String {
+    // serde_json objects keep their keys sorted, so this is the canonical form RFC 7638 requires
+    let canonical = json::to_json_object(members).to_string();
+    util::bytes_to_base64url(Sha256::digest(canonical.as_bytes()))
+}
+
+/**
+ * Compute the RFC 7638 thumbprint of the JWK for the provided recrypt public key.
+ */
+pub fn public_key_thumbprint(public_key: &PublicKey) -> String {
+    let (x, y) = public_key.bytes_x_y();
+    thumbprint(vec![
+        ("crv", CRV_FP256.into()),
+        ("kty", KTY_RECRYPT.into()),
+        ("x", util::bytes_to_base64url(x).into()),
+        ("y", util::bytes_to_base64url(y).into()),
+    ])
+}
+
+/**
+ * Compute the RFC 7638 thumbprint of the JWK for the provided Ed25519 public key.
+ */
+pub fn signing_public_key_thumbprint(public_key: &[u8; 32]) -> String {
+    thumbprint(vec![
+        ("crv", CRV_ED25519.into()),
+        ("kty", KTY_OKP.into()),
+        ("x", util::bytes_to_base64url(public_key).into()),
+    ])
+}
@@ -1,8 +1,20 @@
-#[macro_use]
-extern crate serde_derive;
 extern crate rand;
 extern crate recrypt;
 extern crate wasm_bindgen;
 
 pub mod api256;
+mod cbor;
+mod derivation;
+mod diagnostics;
+mod encoding;
+pub mod handles;
+mod identity;
+mod json;
+mod jwe;
+mod jwk;
+mod password;
+mod pkcs8;
+mod protobuf;
+mod secrets;
 mod util;
+pub mod values;
@@ -0,0 +1,385 @@
+//! Password based wrapping of a recrypt private key, and optionally an Ed25519 signing key, for escrowing a user's keys
+//! protected by their passcode.
+//!
+//! A 32 byte key is derived from the password and a random 16 byte salt with Argon2id (version 0x13). It encrypts the 32
+//! byte private key, followed by the 32 byte Ed25519 seed when a signing key is included, with AES-256-GCM under a random
+//! 96 bit IV. Wrapped keys use the version and kind header from src/encoding.rs, with kind 3:
+//!
+//! | Field                                            | Size     |
+//! |--------------------------------------------------|----------|
+//! | version, kind                                    | 2        |
+//! | memoryCost, in KiB                               | 4        |
+//! | iterations                                       | 4        |
+//! | parallelism                                      | 4        |
+//! | salt                                             | 16       |
+//! | iv                                               | 12       |
+//! | publicKey                                        | 64       |
+//! | contents, 1 for a private key, 2 with a seed     | 1        |
+//! | ciphertext                                       | 32 or 64 |
+//! | tag                                              | 16       |
+//!
+//! Everything before the ciphertext is the AES-GCM additional data, so the Argon2 parameters and public key can't be
+//! changed without unwrapping failing. The public key lets callers tell which key a wrapped key holds without the
+//! password. Unwrapping recomputes it from the decrypted private key and fails if it doesn't match.
+//!
+//! The Argon2 parameters are limited to at most 1GiB of memory, 100 iterations and a parallelism of 16 so that a wrapped
+//! key from an untrusted source can't make unwrapping run out of memory or run for hours.
+use crate::encoding::{self, ByteReader};
+use crate::util::{self, BindingError, JsError};
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce, Tag, aead::AeadInPlace};
+use argon2::{Algorithm, Argon2, Block, Params, Version};
+use recrypt::api::{PrivateKey, PublicKey, SigningKeypair};
+use wasm_bindgen::JsValue;
+use zeroize::Zeroizing;
+
+const CONTENTS_PRIVATE_KEY: u8 = 1;
+const CONTENTS_PRIVATE_KEY_AND_SEED: u8 = 2;
+const SALT_SIZE: usize = 16;
+const TAG_SIZE: usize = 16;
+
+const MIN_ITERATIONS: u32 = 1;
+const MAX_ITERATIONS: u32 = 100;
+const MIN_PARALLELISM: u32 = 1;
+const MAX_PARALLELISM: u32 = 16;
+// Argon2 needs at least 8 KiB of memory per lane
+const MIN_MEMORY_COST_PER_LANE: u32 = 8;
+const MAX_MEMORY_COST: u32 = 1024 * 1024;
+
+/**
+ * The tunable Argon2id costs. The defaults are the OWASP recommendation of 19MiB of memory and 2 iterations.
+ */
+pub struct Argon2Costs {
+    pub memory_cost: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Argon2Costs {
+    const DEFAULT: Argon2Costs = Argon2Costs {
+        memory_cost: 19 * 1024,
+        iterations: 2,
+        parallelism: 1,
+    };
+
+    /**
+     * Read the costs from an optional options object with memoryCost, iterations and parallelism properties, using the
+     * default for any which aren't provided. The field is the path to the options object.
+     */
+    pub fn from_js(options: &JsValue, field: &str) -> Result<Argon2Costs, BindingError> {
+        if options.is_undefined() || options.is_null() {
+            return Ok(Argon2Costs::DEFAULT);
+        }
+        let cost = |name: &str, default: u32, min: u32, max: u32, expected: &'static str| {
+            if !util::has_js_property(options, name) {
+                return Ok(default);
+            }
+            let value = util::get_js_property(options, name, field)?;
+            match value.as_f64() {
+                Some(cost) if cost.fract() == 0.0 && cost >= min as f64 && cost <= max as f64 => {
+                    Ok(cost as u32)
+                }
+                _ => Err(BindingError::InvalidType {
+                    field: format!("{}.{}", field, name),
+                    expected,
+                }),
+            }
+        };
+        let parallelism = cost(
+            "parallelism",
+            Argon2Costs::DEFAULT.parallelism,
+            MIN_PARALLELISM,
+            MAX_PARALLELISM,
+            "an integer from 1 to 16",
+        )?;
+        let costs = Argon2Costs {
+            memory_cost: cost(
+                "memoryCost",
+                Argon2Costs::DEFAULT.memory_cost,
+                MIN_MEMORY_COST_PER_LANE * parallelism,
+                MAX_MEMORY_COST,
+                "an integer number of KiB from 8 times parallelism to 1048576",
+            )?,
+            iterations: cost(
+                "iterations",
+                Argon2Costs::DEFAULT.iterations,
+                MIN_ITERATIONS,
+                MAX_ITERATIONS,
+                "an integer from 1 to 100",
+            )?,
+            parallelism,
+        };
+        Ok(costs)
+    }
+
+    /**
+     * Read the costs recorded in a wrapped key, failing if any is outside the limits from_js enforces.
+     */
+    fn read(reader: &mut ByteReader, field: &str) -> Result<Argon2Costs, BindingError> {
+        let memory_cost = reader.read_u32("memoryCost")?;
+        let iterations = reader.read_u32("iterations")?;
+        let parallelism = reader.read_u32("parallelism")?;
+        let out_of_range = |name: &str, value: u32, min: u32, max: u32| {
+            reader.error(
+                name,
+                format!(
+                    "Wrapped key in '{}' has {} {} which is outside the supported range of {} to {}.",
+                    field,
+                    name,
+                    value,
+                    min,
+                    max
+                ),
+            )
+        };
+        if !(MIN_PARALLELISM..=MAX_PARALLELISM).contains(&parallelism) {
+            return Err(out_of_range(
+                "parallelism",
+                parallelism,
+                MIN_PARALLELISM,
+                MAX_PARALLELISM,
+            ));
+        }
+        let min_memory_cost = MIN_MEMORY_COST_PER_LANE * parallelism;
+        if !(min_memory_cost..=MAX_MEMORY_COST).contains(&memory_cost) {
+            return Err(out_of_range(
+                "memoryCost",
+                memory_cost,
+                min_memory_cost,
+                MAX_MEMORY_COST,
+            ));
+        }
+        if !(MIN_ITERATIONS..=MAX_ITERATIONS).contains(&iterations) {
+            return Err(out_of_range(
+                "iterations",
+                iterations,
+                MIN_ITERATIONS,
+                MAX_ITERATIONS,
+            ));
+        }
+        Ok(Argon2Costs {
+            memory_cost,
+            iterations,
+            parallelism,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.memory_cost.to_be_bytes());
+        out.extend_from_slice(&self.iterations.to_be_bytes());
+        out.extend_from_slice(&self.parallelism.to_be_bytes());
+    }
+
+    /**
+     * Derive the AES-256-GCM key from the password and salt.
+     */
+    fn derive_key(
+        &self,
+        password: &[u8],
+        salt: &[u8; SALT_SIZE],
+        field: &str,
+    ) -> Result<Zeroizing<[u8; 32]>, BindingError> {
+        let argon2_error = |e: argon2::Error| BindingError::InvalidEncoding {
+            field: field.to_string(),
+            message: format!("Failed to derive a key from the password: {}", e),
+        };
+        let params = Params::new(
+            self.memory_cost,
+            self.iterations,
+            self.parallelism,
+            Some(32),
+        )
+        .map_err(argon2_error)?;
+        // argon2 frees the work area it allocates without zeroing it, and the last blocks of each lane are enough to
+        // recompute the key, so the work area is provided here and zeroed once the key is derived. The reservation is
+        // fallible so that a large memory cost fails with an error rather than aborting.
+        let mut blocks = Zeroizing::new(Vec::new());
+        blocks
+            .try_reserve_exact(params.block_count())
+            .map_err(|_| BindingError::InvalidEncoding {
+                field: field.to_string(),
+                message: format!(
+                    "Failed to allocate {} KiB of memory to derive a key from the password.",
+                    self.memory_cost
+                ),
+            })?;
+        blocks.resize(params.block_count(), Block::default());
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into_with_memory(
+                password,
+                salt,
+                key.as_mut_slice(),
+                blocks.as_mut_slice(),
+            )
+            .map_err(argon2_error)?;
+        Ok(key)
+    }
+}
+
+/**
+ * Read a password, which is either a string, used as its UTF-8 bytes, or any of the byte forms js_value_to_bytes accepts.
+ * Empty passwords are rejected.
+ */
+pub fn js_value_to_password(
+    value: &JsValue,
+    field: &str,
+) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+    let password = Zeroizing::new(match value.as_string() {
+        Some(password) => password.into_bytes(),
+        None => util::js_value_to_bytes(value, field).map_err(|_| BindingError::InvalidType {
+            field: field.to_string(),
+            expected: "a string or bytes",
+        })?,
+    });
+    if password.is_empty() {
+        return Err(BindingError::InvalidType {
+            field: field.to_string(),
+            expected: "a non-empty string or bytes",
+        });
+    }
+    Ok(password)
+}
+
+/**
+ * A recrypt key pair and optional Ed25519 signing key pair protected by a password.
+ */
+pub struct PasswordWrappedKeys {
+    pub private_key: PrivateKey,
+    pub public_key: PublicKey,
+    pub signing_key_pair: Option<SigningKeypair>,
+}
+
+impl PasswordWrappedKeys {
+    /**
+     * Encrypt the private key, and the signing key pair if one is provided, under a key derived from the password with the
+     * provided costs, in the format described above. The public key must already have been computed from the private key.
+     * Takes the keys by reference so that keys held by handles can be wrapped without copying them out.
+     */
+    pub fn wrap(
+        private_key: &PrivateKey,
+        public_key: &PublicKey,
+        signing_key_pair: Option<&SigningKeypair>,
+        password: &[u8],
+        costs: &Argon2Costs,
+    ) -> Result<Vec<u8>, BindingError> {
+        let salt = util::random_bytes::<SALT_SIZE>()?;
+        let iv = util::random_bytes::<12>()?;
+        let mut out = vec![
+            encoding::FORMAT_VERSION,
+            encoding::PASSWORD_WRAPPED_KEY_KIND,
+        ];
+        costs.write(&mut out);
+        out.extend_from_slice(&salt);
+        out.extend_from_slice(&iv);
+        encoding::write_public_key(&mut out, public_key);
+
+        // Sized for both keys up front so a reallocation doesn't leave a copy of the private key behind
+        let mut contents = Zeroizing::new(Vec::with_capacity(64));
+        contents.extend_from_slice(private_key.bytes());
+        match signing_key_pair {
+            Some(signing_key_pair) => {
+                out.push(CONTENTS_PRIVATE_KEY_AND_SEED);
+                contents.extend_from_slice(util::signing_keypair_seed(signing_key_pair).as_slice());
+            }
+            None => out.push(CONTENTS_PRIVATE_KEY),
+        }
+        let key = costs.derive_key(password, &salt, "password")?;
+        let tag = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+            .encrypt_in_place_detached(Nonce::from_slice(&iv), &out, &mut contents)
+            .map_err(|_| BindingError::InvalidEncoding {
+                field: "privateKey".to_string(),
+                message: "Failed to encrypt the private key.".to_string(),
+            })?;
+        out.extend_from_slice(&contents);
+        out.extend_from_slice(&tag);
+        Ok(out)
+    }
+
+    /**
+     * Decrypt a wrapped key produced by wrap with the password. The public key of the decrypted private key is recomputed
+     * with compute_public_key and must match the one recorded in the wrapped key.
+     */
+    pub fn unwrap<F>(
+        wrapped_key: &[u8],
+        password: &[u8],
+        compute_public_key: F,
+        field: &str,
+    ) -> Result<PasswordWrappedKeys, JsError>
+    where
+        F: FnOnce(&PrivateKey) -> Result<PublicKey, JsError>,
+    {
+        let mut reader = ByteReader::new(wrapped_key, field);
+        reader.read_header(encoding::PASSWORD_WRAPPED_KEY_KIND, "password wrapped key")?;
+        let costs = Argon2Costs::read(&mut reader, field)?;
+        let salt = reader.read::<SALT_SIZE>("salt")?;
+        let iv = reader.read::<12>("iv")?;
+        let public_key = reader.read_public_key("publicKey")?;
+        let [contents_kind] = reader.read::<1>("contents")?;
+        let has_seed = match contents_kind {
+            CONTENTS_PRIVATE_KEY => false,
+            CONTENTS_PRIVATE_KEY_AND_SEED => true,
+            _ => {
+                return Err(reader
+                    .error(
+                        "contents",
+                        format!(
+                            "Wrapped key in '{}' has contents {} but expected {} or {}.",
+                            field,
+                            contents_kind,
+                            CONTENTS_PRIVATE_KEY,
+                            CONTENTS_PRIVATE_KEY_AND_SEED
+                        ),
+                    )
+                    .into());
+            }
+        };
+        let additional_data = &wrapped_key[..wrapped_key.len() - reader.remaining()];
+        let mut contents = Zeroizing::new(reader.read::<32>("ciphertext")?.to_vec());
+        if has_seed {
+            contents.extend_from_slice(&reader.read::<32>("ciphertext")?);
+        }
+        let tag = reader.read::<TAG_SIZE>("tag")?;
+        reader.finish()?;
+
+        let key = costs.derive_key(password, &salt, field)?;
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+            .decrypt_in_place_detached(
+                Nonce::from_slice(&iv),
+                additional_data,
+                &mut contents,
+                Tag::from_slice(&tag),
+            )
+            .map_err(|_| BindingError::DecryptFailed {
+                field: field.to_string(),
+                message: format!(
+                    "Wrapped key in '{}' could not be decrypted. The password is wrong or the wrapped key was modified.",
+                    field
+                ),
+            })?;
+
+        let private_key = PrivateKey::new(util::slice_to_fixed_32_bytes(&contents[..32], field)?);
+        if compute_public_key(&private_key)? != public_key {
+            let public_key_field = format!("{}.publicKey", field);
+            return Err(BindingError::InvalidPublicKey {
+                message: format!(
+                    "Value for '{}' is not the public key of the wrapped private key.",
+                    public_key_field
+                ),
+                field: public_key_field,
+            }
+            .into());
+        }
+        let signing_key_pair = match contents.get(32..) {
+            Some(seed) if !seed.is_empty() => Some(util::seed_to_signing_keypair(
+                &Zeroizing::new(util::slice_to_fixed_32_bytes(seed, field)?),
+                field,
+            )?),
+            _ => None,
+        };
+        Ok(PasswordWrappedKeys {
+            private_key,
+            public_key,
+            signing_key_pair,
+        })
+    }
+}
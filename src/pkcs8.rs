@@ -0,0 +1,308 @@
+//! PKCS#8 (RFC 5958) and SubjectPublicKeyInfo (RFC 5280) encodings of Ed25519 signing keys, as profiled by RFC 8410, in
+//! DER and PEM. These are the forms WebCrypto, Node's `crypto.createPrivateKey`/`createPublicKey` and OpenSSL load.
+//!
+//! Private keys are exported as a version 1 PrivateKeyInfo holding the 32 byte seed, which every one of those accepts:
+//!
+//! ```text
+//! 30 2e 02 01 00 30 05 06 03 2b 65 70 04 22 04 20 <32 byte seed>
+//! ```
+//!
+//! Importing also accepts version 2 (OneAsymmetricKey) with attributes and the optional public key, which must match the
+//! seed. Public keys are always:
+//!
+//! ```text
+//! 30 2a 30 05 06 03 2b 65 70 03 21 00 <32 byte public key>
+//! ```
+use crate::util::{self, BindingError, JsError};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use recrypt::api::SigningKeypair;
+use zeroize::Zeroizing;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_ATTRIBUTES: u8 = 0xa0;
+const TAG_PUBLIC_KEY: u8 = 0x81;
+// 1.3.101.112, id-Ed25519
+const OID_ED25519: [u8; 3] = [0x2b, 0x65, 0x70];
+
+const PEM_PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+const PEM_PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+/**
+ * Encode a single DER element. Every element written here is shorter than 128 bytes, so the short form length is
+ * always used.
+ */
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag, contents.len() as u8];
+    element.extend_from_slice(contents);
+    element
+}
+
+fn ed25519_algorithm_identifier() -> Vec<u8> {
+    der(TAG_SEQUENCE, &der(TAG_OID, &OID_ED25519))
+}
+
+/**
+ * Reads DER elements (tag, length, contents) in order from a byte slice.
+ */
+struct DerReader<'a> {
+    bytes: &'a [u8],
+    field: &'a str,
+}
+
+impl<'a> DerReader<'a> {
+    fn new(bytes: &'a [u8], field: &'a str) -> DerReader<'a> {
+        DerReader { bytes, field }
+    }
+
+    fn error(&self, message: &str) -> BindingError {
+        BindingError::InvalidEncoding {
+            field: self.field.to_string(),
+            message: format!("DER value in '{}' is invalid: {}", self.field, message),
+        }
+    }
+
+    fn peek_tag(&self) -> Option<u8> {
+        self.bytes.first().copied()
+    }
+
+    /**
+     * Read the next element, which must have the provided tag, and return its contents.
+     */
+    fn expect(&mut self, tag: u8, name: &str) -> Result<&'a [u8], BindingError> {
+        let truncated = || self.error(&format!("{} is truncated.", name));
+        match self.bytes {
+            [actual, ..] if *actual != tag => {
+                return Err(self.error(&format!(
+                    "expected {} with tag 0x{:02x} but found tag 0x{:02x}.",
+                    name, tag, actual
+                )));
+            }
+            [] => return Err(truncated()),
+            _ => (),
+        }
+        let (length, header_size) = match self.bytes.get(1..) {
+            Some([length @ 0..=0x7f, ..]) => (*length as usize, 2),
+            Some([0x81, length @ 0x80..=0xff, ..]) => (*length as usize, 3),
+            Some([0x82, high @ 0x01..=0xff, low, ..]) => {
+                (u16::from_be_bytes([*high, *low]) as usize, 4)
+            }
+            Some([0x80..=0xff, ..]) => {
+                return Err(self.error(&format!(
+                    "{} has a length which is not minimally encoded or is too large.",
+                    name
+                )));
+            }
+            _ => return Err(truncated()),
+        };
+        let contents = self
+            .bytes
+            .get(header_size..header_size + length)
+            .ok_or_else(truncated)?;
+        self.bytes = &self.bytes[header_size + length..];
+        Ok(contents)
+    }
+
+    fn finish(self) -> Result<(), BindingError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(&format!("{} unexpected trailing bytes.", self.bytes.len())))
+        }
+    }
+}
+
+/**
+ * Read an AlgorithmIdentifier and check that it is Ed25519 with no parameters, as RFC 8410 requires.
+ */
+fn read_ed25519_algorithm(reader: &mut DerReader, field: &str) -> Result<(), BindingError> {
+    let mut algorithm = DerReader::new(reader.expect(TAG_SEQUENCE, "AlgorithmIdentifier")?, field);
+    if algorithm.expect(TAG_OID, "algorithm")? != OID_ED25519 {
+        return Err(reader.error("the key algorithm is not Ed25519 (1.3.101.112)."));
+    }
+    algorithm.finish()
+}
+
+/**
+ * Encode the provided signing key pair as a version 1 PKCS#8 PrivateKeyInfo in DER.
+ */
+pub fn encode_pkcs8(signing_key_pair: &SigningKeypair) -> Zeroizing<Vec<u8>> {
+    let seed = util::signing_keypair_seed(signing_key_pair);
+    let private_key = Zeroizing::new(der(
+        TAG_OCTET_STRING,
+        &Zeroizing::new(der(TAG_OCTET_STRING, seed.as_slice())),
+    ));
+    let mut contents = Zeroizing::new(der(TAG_INTEGER, &[0]));
+    contents.extend(ed25519_algorithm_identifier());
+    // Any reallocation happens before the seed is copied in, so no copy of it is left in the old buffer
+    contents.extend_from_slice(&private_key);
+    Zeroizing::new(der(TAG_SEQUENCE, &contents))
+}
+
+/**
+ * Decode a DER PKCS#8 PrivateKeyInfo or OneAsymmetricKey holding an Ed25519 seed into a signing key pair. If the public
+ * key is included it must match the seed. The field is used in any error.
+ */
+pub fn decode_pkcs8(bytes: &[u8], field: &str) -> Result<SigningKeypair, JsError> {
+    let mut outer = DerReader::new(bytes, field);
+    let mut key_info = DerReader::new(outer.expect(TAG_SEQUENCE, "PrivateKeyInfo")?, field);
+    outer.finish()?;
+    let version = key_info.expect(TAG_INTEGER, "version")?;
+    if version != [0] && version != [1] {
+        return Err(key_info
+            .error("only versions 1 and 2 are supported.")
+            .into());
+    }
+    read_ed25519_algorithm(&mut key_info, field)?;
+    let mut private_key = DerReader::new(key_info.expect(TAG_OCTET_STRING, "privateKey")?, field);
+    let seed = util::slice_to_fixed_32_bytes(
+        private_key.expect(TAG_OCTET_STRING, "CurvePrivateKey")?,
+        field,
+    )?;
+    private_key.finish()?;
+    if key_info.peek_tag() == Some(TAG_ATTRIBUTES) {
+        key_info.expect(TAG_ATTRIBUTES, "attributes")?;
+    }
+    let public_key = if version == [1] && key_info.peek_tag() == Some(TAG_PUBLIC_KEY) {
+        match key_info.expect(TAG_PUBLIC_KEY, "publicKey")? {
+            [0, public_key @ ..] => Some(util::slice_to_fixed_32_bytes(public_key, field)?),
+            _ => return Err(key_info.error("publicKey has unused bits.").into()),
+        }
+    } else {
+        None
+    };
+    key_info.finish()?;
+
+    let signing_key_pair = util::seed_to_signing_keypair(&seed, field)?;
+    match public_key {
+        Some(public_key) if &public_key != signing_key_pair.public_key().bytes() => {
+            Err(BindingError::InvalidSigningKeyPair {
+                field: field.to_string(),
+                message:
+                    "The public key does not match the public half of the private signing key."
+                        .to_string(),
+            }
+            .into())
+        }
+        _ => Ok(signing_key_pair),
+    }
+}
+
+/**
+ * Encode the provided Ed25519 public key as a SubjectPublicKeyInfo in DER.
+ */
+pub fn encode_spki(public_key: &[u8; 32]) -> Vec<u8> {
+    let mut contents = ed25519_algorithm_identifier();
+    let mut bit_string = vec![0];
+    bit_string.extend_from_slice(public_key);
+    contents.extend(der(TAG_BIT_STRING, &bit_string));
+    der(TAG_SEQUENCE, &contents)
+}
+
+/**
+ * Decode a DER SubjectPublicKeyInfo holding an Ed25519 public key, checking that the key is a valid curve point. The
+ * field is used in any error.
+ */
+pub fn decode_spki(bytes: &[u8], field: &str) -> Result<[u8; 32], BindingError> {
+    let mut outer = DerReader::new(bytes, field);
+    let mut key_info = DerReader::new(outer.expect(TAG_SEQUENCE, "SubjectPublicKeyInfo")?, field);
+    outer.finish()?;
+    read_ed25519_algorithm(&mut key_info, field)?;
+    let public_key = match key_info.expect(TAG_BIT_STRING, "subjectPublicKey")? {
+        [0, public_key @ ..] => util::slice_to_fixed_32_bytes(public_key, field)?,
+        _ => return Err(key_info.error("subjectPublicKey has unused bits.")),
+    };
+    key_info.finish()?;
+    ed25519_dalek::VerifyingKey::from_bytes(&public_key).map_err(|_| {
+        BindingError::InvalidSigningKeyPair {
+            field: field.to_string(),
+            message: format!("Value in '{}' is not a valid Ed25519 public key.", field),
+        }
+    })?;
+    Ok(public_key)
+}
+
+/**
+ * Wrap DER bytes in a PEM block with the provided label, using 64 character lines. The block is built in a buffer
+ * allocated at its final size, so private keys don't leave partial copies behind in reallocated buffers.
+ */
+fn to_pem(label: &str, der: &[u8]) -> Zeroizing<String> {
+    let encoded = Zeroizing::new(STANDARD.encode(der));
+    let begin = format!("-----BEGIN {}-----\n", label);
+    let end = format!("-----END {}-----\n", label);
+    let mut pem = Zeroizing::new(String::with_capacity(
+        begin.len() + encoded.len() + encoded.len().div_ceil(64) + end.len(),
+    ));
+    pem.push_str(&begin);
+    for line in encoded.as_bytes().chunks(64) {
+        // Base64 output is always ASCII
+        pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+        pem.push('\n');
+    }
+    pem.push_str(&end);
+    pem
+}
+
+/**
+ * Extract the DER bytes from a PEM block with the provided label.
+ */
+fn from_pem(pem: &str, label: &str, field: &str) -> Result<Zeroizing<Vec<u8>>, BindingError> {
+    let invalid = |message: String| BindingError::InvalidEncoding {
+        field: field.to_string(),
+        message,
+    };
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let body = pem
+        .trim()
+        .strip_prefix(&begin)
+        .and_then(|rest| rest.strip_suffix(&end))
+        .ok_or_else(|| {
+            invalid(format!(
+                "Value for '{}' is not a PEM block starting with '{}' and ending with '{}'.",
+                field, begin, end
+            ))
+        })?;
+    let mut base64 = Zeroizing::new(String::with_capacity(body.len()));
+    base64.extend(body.chars().filter(|c| !c.is_ascii_whitespace()));
+    STANDARD
+        .decode(base64.as_bytes())
+        .map(Zeroizing::new)
+        .map_err(|e| {
+            invalid(format!(
+                "Value for '{}' does not contain valid base64: {}",
+                field, e
+            ))
+        })
+}
+
+/**
+ * Same as encode_pkcs8, but as a PEM "PRIVATE KEY" block.
+ */
+pub fn encode_pkcs8_pem(signing_key_pair: &SigningKeypair) -> Zeroizing<String> {
+    to_pem(PEM_PRIVATE_KEY_LABEL, &encode_pkcs8(signing_key_pair))
+}
+
+/**
+ * Same as decode_pkcs8, but for a PEM "PRIVATE KEY" block.
+ */
+pub fn decode_pkcs8_pem(pem: &str, field: &str) -> Result<SigningKeypair, JsError> {
+    decode_pkcs8(&from_pem(pem, PEM_PRIVATE_KEY_LABEL, field)?, field)
+}
+
+/**
+ * Same as encode_spki, but as a PEM "PUBLIC KEY" block.
+ */
+pub fn encode_spki_pem(public_key: &[u8; 32]) -> String {
+    to_pem(PEM_PUBLIC_KEY_LABEL, &encode_spki(public_key)).to_string()
+}
+
+/**
+ * Same as decode_spki, but for a PEM "PUBLIC KEY" block.
+ */
+pub fn decode_spki_pem(pem: &str, field: &str) -> Result<[u8; 32], BindingError> {
+    decode_spki(&from_pem(pem, PEM_PUBLIC_KEY_LABEL, field)?, field)
+}
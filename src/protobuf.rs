@@ -0,0 +1,193 @@
+//! Protobuf encodings of encrypted values and transform keys. The messages below are the Rust side of
+//! proto/recrypt.proto and must be kept in-sync with it.
+//!
+//! Decoding converts the message into the JS object form and runs it through the same checks as any other value provided
+//! from JS, so sizes and public keys are validated the same way and errors report the JS property path of the bad field.
+use crate::util::{
+    self, BindingError, JsEncryptedValue, JsError, JsPublicKey, JsTransformBlock, JsTransformKey,
+};
+use prost::Message;
+use recrypt::api::{EncryptedValue, TransformKey};
+use wasm_bindgen::JsValue;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PublicKey {
+    #[prost(bytes = "vec", tag = "1")]
+    pub x: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub y: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TransformBlock {
+    #[prost(message, optional, tag = "1")]
+    pub public_key: Option<PublicKey>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub encrypted_temp_key: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub random_transform_public_key: Option<PublicKey>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub random_transform_encrypted_temp_key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct EncryptedValueMessage {
+    #[prost(message, optional, tag = "1")]
+    pub ephemeral_public_key: Option<PublicKey>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub encrypted_message: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub auth_hash: Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub transform_blocks: Vec<TransformBlock>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub public_signing_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub signature: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TransformKeyMessage {
+    #[prost(message, optional, tag = "1")]
+    pub ephemeral_public_key: Option<PublicKey>,
+    #[prost(message, optional, tag = "2")]
+    pub to_public_key: Option<PublicKey>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub encrypted_temp_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub hashed_temp_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub public_signing_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub signature: Vec<u8>,
+}
+
+impl From<JsPublicKey> for PublicKey {
+    fn from(public_key: JsPublicKey) -> PublicKey {
+        PublicKey {
+            x: public_key.x,
+            y: public_key.y,
+        }
+    }
+}
+
+/**
+ * Convert an optional public key message into a JsPublicKey. Proto3 can't require a message field, so a missing public
+ * key is reported the same way as a missing property on a JS object.
+ */
+fn required_public_key(
+    public_key: Option<PublicKey>,
+    field: &str,
+) -> Result<JsPublicKey, BindingError> {
+    public_key
+        .map(|PublicKey { x, y }| JsPublicKey { x, y })
+        .ok_or_else(|| BindingError::MissingField(field.to_string()))
+}
+
+fn decode_message<M: Message + Default>(bytes: &[u8], field: &str) -> Result<M, BindingError> {
+    M::decode(bytes).map_err(|e| BindingError::InvalidEncoding {
+        field: field.to_string(),
+        message: format!("Protobuf value in '{}' could not be decoded: {}", field, e),
+    })
+}
+
+/**
+ * Encode the provided EncryptedValue as an EncryptedValue protobuf message.
+ */
+pub fn encode_encrypted_value(encrypted_value: EncryptedValue) -> Vec<u8> {
+    let js_value = util::encrypted_value_to_js_object(encrypted_value);
+    EncryptedValueMessage {
+        ephemeral_public_key: Some(js_value.ephemeralPublicKey.into()),
+        encrypted_message: js_value.encryptedMessage,
+        auth_hash: js_value.authHash,
+        transform_blocks: js_value
+            .transformBlocks
+            .into_iter()
+            .map(|block| TransformBlock {
+                public_key: Some(block.publicKey.into()),
+                encrypted_temp_key: block.encryptedTempKey,
+                random_transform_public_key: Some(block.randomTransformPublicKey.into()),
+                random_transform_encrypted_temp_key: block.randomTransformEncryptedTempKey,
+            })
+            .collect(),
+        public_signing_key: js_value.publicSigningKey,
+        signature: js_value.signature,
+    }
+    .encode_to_vec()
+}
+
+/**
+ * Decode an EncryptedValue protobuf message and validate it.
+ */
+pub fn decode_encrypted_value(bytes: &[u8], field: &str) -> Result<EncryptedValue, JsError> {
+    let message: EncryptedValueMessage = decode_message(bytes, field)?;
+    let transform_blocks = message
+        .transform_blocks
+        .into_iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let block_field = format!("{}.transformBlocks[{}]", field, index);
+            Ok(JsTransformBlock {
+                publicKey: required_public_key(
+                    block.public_key,
+                    &format!("{}.publicKey", block_field),
+                )?,
+                encryptedTempKey: block.encrypted_temp_key,
+                randomTransformPublicKey: required_public_key(
+                    block.random_transform_public_key,
+                    &format!("{}.randomTransformPublicKey", block_field),
+                )?,
+                randomTransformEncryptedTempKey: block.random_transform_encrypted_temp_key,
+            })
+        })
+        .collect::<Result<Vec<_>, BindingError>>()?;
+    let js_value: JsValue = JsEncryptedValue {
+        ephemeralPublicKey: required_public_key(
+            message.ephemeral_public_key,
+            &format!("{}.ephemeralPublicKey", field),
+        )?,
+        encryptedMessage: message.encrypted_message,
+        authHash: message.auth_hash,
+        publicSigningKey: message.public_signing_key,
+        signature: message.signature,
+        transformBlocks: transform_blocks,
+    }
+    .into();
+    util::js_object_to_encrypted_value(&js_value, field)
+}
+
+/**
+ * Encode the provided TransformKey as a TransformKey protobuf message.
+ */
+pub fn encode_transform_key(transform_key: TransformKey) -> Vec<u8> {
+    let js_key = util::transform_key_to_js_object(transform_key);
+    TransformKeyMessage {
+        ephemeral_public_key: Some(js_key.ephemeralPublicKey.into()),
+        to_public_key: Some(js_key.toPublicKey.into()),
+        encrypted_temp_key: js_key.encryptedTempKey,
+        hashed_temp_key: js_key.hashedTempKey,
+        public_signing_key: js_key.publicSigningKey,
+        signature: js_key.signature,
+    }
+    .encode_to_vec()
+}
+
+/**
+ * Decode a TransformKey protobuf message and validate it.
+ */
+pub fn decode_transform_key(bytes: &[u8], field: &str) -> Result<TransformKey, JsError> {
+    let message: TransformKeyMessage = decode_message(bytes, field)?;
+    let js_key: JsValue = JsTransformKey {
+        ephemeralPublicKey: required_public_key(
+            message.ephemeral_public_key,
+            &format!("{}.ephemeralPublicKey", field),
+        )?,
+        toPublicKey: required_public_key(message.to_public_key, &format!("{}.toPublicKey", field))?,
+        encryptedTempKey: message.encrypted_temp_key,
+        hashedTempKey: message.hashed_temp_key,
+        publicSigningKey: message.public_signing_key,
+        signature: message.signature,
+    }
+    .into();
+    util::js_object_to_transform_key(&js_key, field)
+}
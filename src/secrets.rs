@@ -0,0 +1,50 @@
+//! Clearing private keys, signing keys, plaintexts and symmetric keys out of WASM linear memory once a call is done with
+//! them. Anything left behind in linear memory can be read back out of the `WebAssembly.Memory` buffer from JS.
+//!
+//! Secrets end up in two places:
+//!
+//! - The heap. Buffers which hold secrets, including the copies of inputs and the JSON, CBOR and base64 forms of keys,
+//!   are wrapped in `Zeroizing` so they are zeroed when they are dropped. Secrets are returned to JS as a Uint8Array or
+//!   string built from a zeroized buffer rather than as a Vec or String, because wasm-bindgen frees those without
+//!   zeroing them. Other buffers, such as the messages passed to ed25519Sign or hash256, are freed as usual, so large
+//!   messages don't pay for zeroing. Buffers recrypt allocates itself are out of reach, and it copies plaintexts into
+//!   several while converting them to and from bytes, so those copies can remain until the memory is reused.
+//! - The shadow stack, which holds any local that is too large for a WASM local or has its address taken. Rust moves
+//!   values by copying them, so a key can be in several stack slots by the time a call returns, and recrypt's own
+//!   clearing on drop writes to memory that is about to go out of scope, which the compiler is free to remove. Calls
+//!   which handle secrets clear the region of the stack they used with clear_stack once they return.
+use zeroize::Zeroize;
+
+/**
+ * The number of bytes of stack cleared below the current frame. transform, the deepest call, uses more than 32KB in
+ * release builds.
+ */
+const STACK_CLEAR_SIZE: usize = 64 * 1024;
+
+/**
+ * Zero the region of the stack below the caller's frame, where the frames of any calls it has already returned from
+ * were. Must not be inlined so that its own frame lands in that region.
+ */
+#[inline(never)]
+pub fn clear_stack() {
+    let mut stack = [0u64; STACK_CLEAR_SIZE / 8];
+    stack.zeroize();
+}
+
+/**
+ * Run the provided call in its own frame. Keeps the call from being inlined into the caller, where its locals would be
+ * above the region clear_stack clears.
+ */
+#[inline(never)]
+fn run<T, F: FnOnce() -> T>(call: F) -> T {
+    call()
+}
+
+/**
+ * Run the provided call and clear the stack it used once it returns.
+ */
+pub fn with_cleared_stack<T, F: FnOnce() -> T>(call: F) -> T {
+    let result = run(call);
+    clear_stack();
+    result
+}
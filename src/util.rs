@@ -1,49 +1,334 @@
 #![allow(non_snake_case)]
+use crate::encoding;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::{Signer, Verifier};
+use js_sys::{Array, ArrayBuffer, JsString, Object, Reflect, Uint8Array};
 use recrypt::{
     api::{
-        AuthHash, Ed25519Signature, EncryptedMessage, EncryptedTempKey, EncryptedValue,
-        HashedValue, PrivateKey, PublicKey, PublicSigningKey, SigningKeypair, TransformBlock,
-        TransformKey,
+        AuthHash, Ed25519Signature, EncryptedMessage, EncryptedTempKey, EncryptedValue, Hashable,
+        HashedValue, PrivateKey, PublicKey, PublicSigningKey, RecryptErr, SigningKeypair,
+        TransformBlock, TransformKey,
     },
-    nonemptyvec::NonEmptyVec,
+    nonemptyvec::{NonEmptyVec, NonEmptyVecError},
 };
+use wasm_bindgen::{JsCast, JsValue};
+use zeroize::Zeroizing;
 
-//Error handling to make it so we can get actual Error instances out of WASM when methods throw with actual messages
-pub type JsError = wasm_bindgen::prelude::JsValue;
+//Error handling to make it so we can get actual Error instances out of WASM when methods throw with actual messages. Each
+//Error that is thrown has a stable `code` property that callers can switch on, as well as a `field` property when the error
+//can be attributed to a specific input value.
+pub type JsError = JsValue;
 pub struct WasmError<E> {
     error: E,
+    field: Option<String>,
 }
 
 impl<E> WasmError<E> {
     pub fn new(error: E) -> WasmError<E> {
-        WasmError { error }
+        WasmError { error, field: None }
     }
+
+    /**
+     * Create an error which is attributed to the provided input field. Takes precedence over any field that the
+     * underlying error reports.
+     */
+    pub fn with_field(error: E, field: &str) -> WasmError<E> {
+        WasmError {
+            error,
+            field: Some(field.to_string()),
+        }
+    }
+}
+
+/**
+ * Create a JS Error with the provided message, setting the stable `code` property and the `field` property if there is one.
+ */
+fn new_js_error(message: &str, code: &str, field: Option<String>) -> JsError {
+    let js_error = js_sys::Error::new(message);
+    // Setting properties on a freshly created Error instance cannot fail
+    let _ = js_sys::Reflect::set(&js_error, &"code".into(), &code.into());
+    if let Some(field) = field {
+        let _ = js_sys::Reflect::set(&js_error, &"field".into(), &field.into());
+    }
+    js_error.into()
 }
-impl<E: core::fmt::Display> From<WasmError<E>> for JsError {
+
+impl<E: core::fmt::Display + ErrorCode> From<WasmError<E>> for JsError {
     fn from(error: WasmError<E>) -> JsError {
-        js_sys::Error::new(&format!("{}", error.error)[..]).into()
+        Diagnostic::from(error).into()
+    }
+}
+
+/**
+ * Stable, machine readable error codes which are exposed as the `code` property on errors thrown into JS. These values
+ * are part of the public API and are listed in `RecryptErrorCode` in recrypt_wasm_binding.d.ts.
+ */
+pub trait ErrorCode {
+    fn code(&self) -> &'static str;
+
+    fn field(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl ErrorCode for RecryptErr {
+    fn code(&self) -> &'static str {
+        match self {
+            RecryptErr::DecryptFailed(_) => "DecryptFailed",
+            RecryptErr::InvalidEncryptedMessageSignature(_) => "InvalidEncryptedMessageSignature",
+            RecryptErr::InvalidPublicKey(_) => "InvalidPublicKey",
+            RecryptErr::InvalidTransformKey(_) => "InvalidTransformKey",
+            RecryptErr::InputWrongSize(_, _) => "InputWrongSize",
+            RecryptErr::DecodeFailure(_) => "DecodeFailure",
+        }
+    }
+}
+
+impl ErrorCode for NonEmptyVecError {
+    fn code(&self) -> &'static str {
+        "DecodeFailure"
+    }
+}
+
+/**
+ * Errors which originate in this binding rather than in recrypt, such as a byte value provided from JS being the
+ * wrong length. The field is the full path to the offending value, e.g. `encryptedValue.transformBlocks[1].publicKey.x`.
+ * Functions which decode or validate a value take a `field` argument naming that value, and build the path of any error
+ * they report by appending to it, so callers get paths which start from the name of their own argument.
+ */
+#[derive(Debug)]
+pub enum BindingError {
+    InputWrongSize {
+        field: String,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidSigningKeyPair {
+        field: String,
+        message: String,
+    },
+    InvalidType {
+        field: String,
+        expected: &'static str,
+    },
+    MissingField(String),
+    InvalidSearchInput(String),
+    InvalidEncoding {
+        field: String,
+        message: String,
+    },
+    InvalidPublicKey {
+        field: String,
+        message: String,
+    },
+    DecryptFailed {
+        field: String,
+        message: String,
+    },
+    RandomFailure(String),
+}
+
+impl core::fmt::Display for BindingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            BindingError::InputWrongSize {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Provided value for '{}' is not of expected size of {} bytes. Instead got {} bytes.",
+                field, expected, actual
+            ),
+            BindingError::InvalidSigningKeyPair { message, .. } => write!(f, "{}", message),
+            BindingError::InvalidType { field, expected } => write!(
+                f,
+                "Provided value for '{}' was not of the expected type. Expected {}.",
+                field, expected
+            ),
+            BindingError::MissingField(field) => {
+                write!(f, "Required value '{}' was not provided.", field)
+            }
+            BindingError::InvalidSearchInput(message) => write!(f, "{}", message),
+            BindingError::InvalidEncoding { message, .. } => write!(f, "{}", message),
+            BindingError::InvalidPublicKey { message, .. } => write!(f, "{}", message),
+            BindingError::DecryptFailed { message, .. } => write!(f, "{}", message),
+            BindingError::RandomFailure(message) => {
+                write!(f, "Failed to generate random bytes: {}", message)
+            }
+        }
+    }
+}
+
+impl ErrorCode for BindingError {
+    fn code(&self) -> &'static str {
+        match self {
+            BindingError::InputWrongSize { .. } => "InputWrongSize",
+            BindingError::InvalidSigningKeyPair { .. } => "InvalidSigningKeyPair",
+            BindingError::InvalidType { .. } | BindingError::MissingField(_) => "SerdeError",
+            BindingError::InvalidSearchInput(_) => "InvalidSearchInput",
+            BindingError::InvalidEncoding { .. } => "DecodeFailure",
+            BindingError::InvalidPublicKey { .. } => "InvalidPublicKey",
+            BindingError::DecryptFailed { .. } => "DecryptFailed",
+            BindingError::RandomFailure(_) => "RandomFailure",
+        }
+    }
+
+    fn field(&self) -> Option<&str> {
+        match self {
+            BindingError::InputWrongSize { field, .. } => Some(field),
+            BindingError::InvalidSigningKeyPair { field, .. } => Some(field),
+            BindingError::InvalidType { field, .. } => Some(field),
+            BindingError::MissingField(field) => Some(field),
+            BindingError::InvalidSearchInput(_) => None,
+            BindingError::InvalidEncoding { field, .. } => Some(field),
+            BindingError::InvalidPublicKey { field, .. } => Some(field),
+            BindingError::DecryptFailed { field, .. } => Some(field),
+            BindingError::RandomFailure(_) => None,
+        }
+    }
+}
+
+impl From<BindingError> for JsError {
+    fn from(error: BindingError) -> JsError {
+        WasmError::new(error).into()
+    }
+}
+
+/**
+ * A single problem found with a value provided from JS. Conversions from JS throw the first Diagnostic found as an
+ * Error, while the validate functions return all of them.
+ */
+pub struct Diagnostic {
+    field: Option<String>,
+    code: &'static str,
+    reason: String,
+    expected_size: Option<usize>,
+    actual_size: Option<usize>,
+}
+
+impl<E: core::fmt::Display + ErrorCode> From<WasmError<E>> for Diagnostic {
+    fn from(error: WasmError<E>) -> Diagnostic {
+        Diagnostic {
+            field: error
+                .field
+                .or_else(|| error.error.field().map(|f| f.to_string())),
+            code: error.error.code(),
+            reason: error.error.to_string(),
+            expected_size: None,
+            actual_size: None,
+        }
+    }
+}
+
+impl From<BindingError> for Diagnostic {
+    fn from(error: BindingError) -> Diagnostic {
+        let sizes = match error {
+            BindingError::InputWrongSize {
+                expected, actual, ..
+            } => Some((expected, actual)),
+            _ => None,
+        };
+        Diagnostic {
+            expected_size: sizes.map(|(expected, _)| expected),
+            actual_size: sizes.map(|(_, actual)| actual),
+            ..WasmError::new(error).into()
+        }
+    }
+}
+
+impl From<Diagnostic> for JsError {
+    fn from(diagnostic: Diagnostic) -> JsError {
+        new_js_error(&diagnostic.reason, diagnostic.code, diagnostic.field)
+    }
+}
+
+impl Diagnostic {
+    /**
+     * Convert this diagnostic into a plain JS object with field, code, reason, expectedSize and actualSize properties.
+     * Properties which don't apply to the problem are left undefined.
+     */
+    pub fn to_js_object(&self) -> JsValue {
+        let to_js_size = |size: Option<usize>| size.map_or(JsValue::UNDEFINED, |s| s.into());
+        new_js_object(&[
+            (
+                "field",
+                self.field
+                    .as_deref()
+                    .map_or(JsValue::UNDEFINED, JsValue::from_str),
+            ),
+            ("code", self.code.into()),
+            ("reason", self.reason.as_str().into()),
+            ("expectedSize", to_js_size(self.expected_size)),
+            ("actualSize", to_js_size(self.actual_size)),
+        ])
+    }
+}
+
+/**
+ * Collects the problems found while checking a value provided from JS, so that checking can continue past the first
+ * invalid field.
+ */
+#[derive(Default)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    /**
+     * Record the error of a failed check. Returns the checked value if the check succeeded.
+     */
+    pub fn check<T, E: Into<Diagnostic>>(&mut self, result: Result<T, E>) -> Option<T> {
+        result.map_err(|e| self.0.push(e.into())).ok()
+    }
+
+    /**
+     * Run the provided checks and return the resulting value, or the first problem found as an Error.
+     */
+    pub fn run<T, F: FnOnce(&mut Diagnostics) -> Option<T>>(checks: F) -> Result<T, JsError> {
+        let mut diagnostics = Diagnostics::default();
+        let value = checks(&mut diagnostics);
+        match (diagnostics.0.into_iter().next(), value) {
+            (Some(diagnostic), _) => Err(diagnostic.into()),
+            (None, Some(value)) => Ok(value),
+            (None, None) => unreachable!("checks only fail by recording a diagnostic"),
+        }
+    }
+
+    /**
+     * Run the provided checks and return every problem found.
+     */
+    pub fn collect<T, F: FnOnce(&mut Diagnostics) -> Option<T>>(checks: F) -> Diagnostics {
+        let mut diagnostics = Diagnostics::default();
+        checks(&mut diagnostics);
+        diagnostics
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /**
+     * Convert the problems found into a JS array of diagnostic objects.
+     */
+    pub fn to_js_array(&self) -> Array {
+        self.0.iter().map(Diagnostic::to_js_object).collect()
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
 pub struct JsPublicKey {
     pub x: Vec<u8>,
     pub y: Vec<u8>,
 }
 
-#[derive(Serialize)]
 pub struct JsKeyPair {
-    pub privateKey: [u8; 32],
+    pub privateKey: Zeroizing<[u8; 32]>,
     pub publicKey: JsPublicKey,
 }
 
-#[derive(Serialize)]
 pub struct JsSigningKeyPair {
-    pub privateKey: Vec<u8>,
+    pub privateKey: Zeroizing<Vec<u8>>,
     pub publicKey: Vec<u8>,
 }
 
-#[derive(Serialize, Deserialize)]
 pub struct JsTransformKey {
     pub encryptedTempKey: Vec<u8>,
     pub ephemeralPublicKey: JsPublicKey,
@@ -53,15 +338,13 @@ pub struct JsTransformKey {
     pub toPublicKey: JsPublicKey,
 }
 
-#[derive(Deserialize, Serialize)]
 pub struct JsTransformBlock {
-    publicKey: JsPublicKey,
-    encryptedTempKey: Vec<u8>,
-    randomTransformPublicKey: JsPublicKey,
-    randomTransformEncryptedTempKey: Vec<u8>,
+    pub publicKey: JsPublicKey,
+    pub encryptedTempKey: Vec<u8>,
+    pub randomTransformPublicKey: JsPublicKey,
+    pub randomTransformEncryptedTempKey: Vec<u8>,
 }
 
-#[derive(Serialize, Deserialize)]
 pub struct JsEncryptedValue {
     pub authHash: Vec<u8>,
     pub encryptedMessage: Vec<u8>,
@@ -72,34 +355,463 @@ pub struct JsEncryptedValue {
 }
 
 /**
- * Macro to generate methods to convert various slice types (slices, vectors) into fixed length arrays of
- * varying length.
+ * Build a plain JS object from the provided list of property names and values.
+ */
+pub fn new_js_object(properties: &[(&str, JsValue)]) -> JsValue {
+    let object = Object::new();
+    for (name, value) in properties {
+        // Defining a property on a freshly created plain object cannot fail
+        let _ = Reflect::set(&object, &JsValue::from_str(name), value);
+    }
+    object.into()
+}
+
+/**
+ * Copy the provided bytes out of WASM memory into a new Uint8Array.
+ */
+pub fn bytes_to_js(bytes: &[u8]) -> JsValue {
+    Uint8Array::from(bytes).into()
+}
+
+/**
+ * Copy secret bytes out of WASM memory into a new Uint8Array. Exports return secrets this way instead of as a Vec<u8>,
+ * which wasm-bindgen would free without zeroing, so that the caller's Zeroizing buffer is the only copy in WASM memory.
  */
-macro_rules! slice_to_fixed_bytes { ($($fn_name: ident, $slice_type: ty, $n: expr); *) => {
-    $(pub fn $fn_name(slice: $slice_type, field_name: &str) -> [u8; $n]{
+pub fn secret_bytes_to_js(bytes: &[u8]) -> Uint8Array {
+    Uint8Array::from(bytes)
+}
+
+/**
+ * Same as secret_bytes_to_js, but for secrets held in a string such as a serialized private key.
+ */
+pub fn secret_string_to_js(string: &str) -> JsString {
+    JsString::from(string)
+}
+
+/**
+ * An io::Write which only counts the bytes written to it. Serializations holding secrets are written once to find their
+ * size, then again into a buffer allocated at that size, so that growing the buffer doesn't leave partial copies behind.
+ */
+#[derive(Default)]
+pub struct ByteCounter(pub usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/**
+ * Read the property with the provided name off of the provided JS object. The field is the path to the object and is
+ * used to report the full path to the property if the object or property is invalid.
+ */
+pub fn get_js_property(object: &JsValue, name: &str, field: &str) -> Result<JsValue, BindingError> {
+    expect_js_object(object, field)?;
+    let value = Reflect::get(object, &JsValue::from_str(name)).unwrap_or(JsValue::UNDEFINED);
+    if value.is_undefined() {
+        return Err(BindingError::MissingField(format!("{}.{}", field, name)));
+    }
+    Ok(value)
+}
+
+/**
+ * Check whether the provided JS value is an object with a defined property of the provided name.
+ */
+pub fn has_js_property(object: &JsValue, name: &str) -> bool {
+    object.is_object()
+        && Reflect::get(object, &JsValue::from_str(name)).is_ok_and(|value| !value.is_undefined())
+}
+
+/**
+ * Read the string property with the provided name off of the provided JS object. Errors report the full path to the
+ * property the same way as get_js_property.
+ */
+pub fn get_js_string_property(
+    object: &JsValue,
+    name: &str,
+    field: &str,
+) -> Result<String, BindingError> {
+    js_value_to_string(
+        &get_js_property(object, name, field)?,
+        &format!("{}.{}", field, name),
+    )
+}
+
+/**
+ * Get a Uint8Array view over the memory of a JS BufferSource (Uint8Array, Node Buffer, ArrayBuffer, DataView or any other
+ * typed array view) without copying it. Returns None if the value is not a BufferSource.
+ */
+fn js_value_to_uint8_array(value: &JsValue) -> Option<Uint8Array> {
+    if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+        Some(bytes.clone())
+    } else if let Some(buffer) = value.dyn_ref::<ArrayBuffer>() {
+        Some(Uint8Array::new(buffer))
+    } else if ArrayBuffer::is_view(value) {
+        let view_property = |name: &str| Reflect::get(value, &JsValue::from_str(name)).ok();
+        match (
+            view_property("buffer"),
+            view_property("byteOffset").and_then(|offset| offset.as_f64()),
+            view_property("byteLength").and_then(|length| length.as_f64()),
+        ) {
+            (Some(buffer), Some(offset), Some(length)) => Some(
+                Uint8Array::new_with_byte_offset_and_length(&buffer, offset as u32, length as u32),
+            ),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+fn invalid_bytes_type(field: &str) -> BindingError {
+    BindingError::InvalidType {
+        field: field.to_string(),
+        expected: "a Uint8Array, ArrayBuffer, DataView, Buffer or array of bytes",
+    }
+}
+
+fn js_array_to_bytes(array: &Array, field: &str) -> Result<Vec<u8>, BindingError> {
+    // Allocated up front, as collecting would grow the buffer and leave copies of any private key in the old ones
+    let mut bytes = Vec::with_capacity(array.length() as usize);
+    for byte in array.iter() {
+        match byte.as_f64() {
+            Some(b) if b.fract() == 0.0 && (0.0..=255.0).contains(&b) => bytes.push(b as u8),
+            _ => return Err(invalid_bytes_type(field)),
+        }
+    }
+    Ok(bytes)
+}
+
+/**
+ * Convert a JS value into bytes. Supports anything that is a BufferSource (Uint8Array, Node Buffer, ArrayBuffer, DataView
+ * or any other typed array view) as well as plain arrays of byte values.
+ */
+pub fn js_value_to_bytes(value: &JsValue, field: &str) -> Result<Vec<u8>, BindingError> {
+    if let Some(bytes) = js_value_to_uint8_array(value) {
+        Ok(bytes.to_vec())
+    } else if let Some(array) = value.dyn_ref::<Array>() {
+        js_array_to_bytes(array, field)
+    } else {
+        Err(invalid_bytes_type(field))
+    }
+}
+
+/**
+ * Generate N bytes from the platform CSPRNG (crypto.getRandomValues in the browser and Node).
+ */
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], BindingError> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| BindingError::RandomFailure(e.to_string()))?;
+    Ok(bytes)
+}
+
+/**
+ * Convert a JS value into a string, failing for any value that isn't a string.
+ */
+pub fn js_value_to_string(value: &JsValue, field: &str) -> Result<String, BindingError> {
+    value.as_string().ok_or_else(|| BindingError::InvalidType {
+        field: field.to_string(),
+        expected: "a string",
+    })
+}
+
+/**
+ * Encode the provided bytes as unpadded base64url (RFC 4648 section 5), as used by JSON, JWE and JWK.
+ */
+pub fn bytes_to_base64url<T: AsRef<[u8]>>(bytes: T) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/**
+ * Decode an unpadded base64url string. Padding and characters from the standard base64 alphabet are rejected.
+ */
+pub fn base64url_to_bytes(encoded: &str, field: &str) -> Result<Vec<u8>, BindingError> {
+    URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| BindingError::InvalidEncoding {
+            field: field.to_string(),
+            message: format!(
+                "Value for '{}' is not valid unpadded base64url: {}",
+                field, e
+            ),
+        })
+}
+
+/**
+ * Size of the buffer used to stream bytes out of JS memory by for_each_js_bytes_chunk.
+ */
+const JS_BYTES_CHUNK_SIZE: usize = 64 * 1024;
+
+/**
+ * Stream the bytes of a JS value through the provided function in fixed size chunks. BufferSources are copied out of JS
+ * memory one chunk at a time into a single reused buffer, so the full value is never held in WASM memory. Accepts the
+ * same values as js_value_to_bytes.
+ */
+pub fn for_each_js_bytes_chunk<F: FnMut(&[u8])>(
+    value: &JsValue,
+    field: &str,
+    mut f: F,
+) -> Result<(), BindingError> {
+    if let Some(bytes) = js_value_to_uint8_array(value) {
+        let length = bytes.length();
+        let mut chunk = vec![0u8; (length as usize).min(JS_BYTES_CHUNK_SIZE)];
+        let mut offset = 0;
+        while offset < length {
+            let end = length.min(offset + JS_BYTES_CHUNK_SIZE as u32);
+            let chunk = &mut chunk[..(end - offset) as usize];
+            bytes.subarray(offset, end).copy_to(chunk);
+            f(chunk);
+            offset = end;
+        }
+        Ok(())
+    } else if let Some(array) = value.dyn_ref::<Array>() {
+        f(&js_array_to_bytes(array, field)?);
+        Ok(())
+    } else {
+        Err(invalid_bytes_type(field))
+    }
+}
+
+/**
+ * Hashable wrapper around a borrowed byte slice. Recrypt implements Hashable for Vec<u8> one byte at a time, which
+ * allocates for every byte of the message, so messages are passed to recrypt through this type instead. Hashable can only
+ * return an owned Vec, so recrypt still makes one copy of the message when it hashes it.
+ */
+pub struct HashableSlice<'a>(pub &'a [u8]);
+
+impl Hashable for HashableSlice<'_> {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
+/**
+ * Sign the provided message with the provided signing key pair. Produces the same signature as recrypt's
+ * SigningKeypair::sign, but signs the borrowed message directly instead of first converting it to an owned Vec.
+ */
+pub fn ed25519_sign(signing_key_pair: &SigningKeypair, message: &[u8]) -> [u8; 64] {
+    ed25519_dalek::SigningKey::from_bytes(&signing_keypair_seed(signing_key_pair))
+        .sign(message)
+        .to_bytes()
+}
+
+/**
+ * Verify the provided signature over the borrowed message. Produces the same result as recrypt's PublicSigningKey::verify.
+ */
+pub fn ed25519_verify(public_signing_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    ed25519_dalek::VerifyingKey::from_bytes(public_signing_key)
+        .and_then(|public_key| {
+            public_key.verify(message, &ed25519_dalek::Signature::from_bytes(signature))
+        })
+        .is_ok()
+}
+
+/**
+ * Same as js_value_to_bytes, but treats `undefined` and `null` as a missing optional value.
+ */
+pub fn js_value_to_optional_bytes(
+    value: &JsValue,
+    field: &str,
+) -> Result<Option<Vec<u8>>, BindingError> {
+    if value.is_undefined() || value.is_null() {
+        Ok(None)
+    } else {
+        js_value_to_bytes(value, field).map(Some)
+    }
+}
+
+impl From<JsPublicKey> for JsValue {
+    fn from(public_key: JsPublicKey) -> JsValue {
+        new_js_object(&[
+            ("x", bytes_to_js(&public_key.x)),
+            ("y", bytes_to_js(&public_key.y)),
+        ])
+    }
+}
+
+impl From<JsKeyPair> for JsValue {
+    fn from(key_pair: JsKeyPair) -> JsValue {
+        new_js_object(&[
+            ("privateKey", bytes_to_js(key_pair.privateKey.as_slice())),
+            ("publicKey", key_pair.publicKey.into()),
+        ])
+    }
+}
+
+impl From<JsSigningKeyPair> for JsValue {
+    fn from(key_pair: JsSigningKeyPair) -> JsValue {
+        new_js_object(&[
+            ("privateKey", bytes_to_js(&key_pair.privateKey)),
+            ("publicKey", bytes_to_js(&key_pair.publicKey)),
+        ])
+    }
+}
+
+impl From<JsTransformKey> for JsValue {
+    fn from(transform_key: JsTransformKey) -> JsValue {
+        new_js_object(&[
+            (
+                "encryptedTempKey",
+                bytes_to_js(&transform_key.encryptedTempKey),
+            ),
+            (
+                "ephemeralPublicKey",
+                transform_key.ephemeralPublicKey.into(),
+            ),
+            ("hashedTempKey", bytes_to_js(&transform_key.hashedTempKey)),
+            (
+                "publicSigningKey",
+                bytes_to_js(&transform_key.publicSigningKey),
+            ),
+            ("signature", bytes_to_js(&transform_key.signature)),
+            ("toPublicKey", transform_key.toPublicKey.into()),
+        ])
+    }
+}
+
+impl From<JsTransformBlock> for JsValue {
+    fn from(block: JsTransformBlock) -> JsValue {
+        new_js_object(&[
+            ("publicKey", block.publicKey.into()),
+            ("encryptedTempKey", bytes_to_js(&block.encryptedTempKey)),
+            (
+                "randomTransformPublicKey",
+                block.randomTransformPublicKey.into(),
+            ),
+            (
+                "randomTransformEncryptedTempKey",
+                bytes_to_js(&block.randomTransformEncryptedTempKey),
+            ),
+        ])
+    }
+}
+
+impl From<JsEncryptedValue> for JsValue {
+    fn from(encrypted_value: JsEncryptedValue) -> JsValue {
+        let transform_blocks: Array = encrypted_value
+            .transformBlocks
+            .into_iter()
+            .map(JsValue::from)
+            .collect();
+        new_js_object(&[
+            ("authHash", bytes_to_js(&encrypted_value.authHash)),
+            (
+                "encryptedMessage",
+                bytes_to_js(&encrypted_value.encryptedMessage),
+            ),
+            (
+                "ephemeralPublicKey",
+                encrypted_value.ephemeralPublicKey.into(),
+            ),
+            (
+                "publicSigningKey",
+                bytes_to_js(&encrypted_value.publicSigningKey),
+            ),
+            ("signature", bytes_to_js(&encrypted_value.signature)),
+            ("transformBlocks", transform_blocks.into()),
+        ])
+    }
+}
+
+/**
+ * Macro to generate methods to convert byte slices into fixed length arrays of varying length. Slices of the wrong
+ * length result in a BindingError which includes the provided field name so the caller can find the bad value.
+ */
+macro_rules! slice_to_fixed_bytes { ($($fn_name: ident, $n: expr); *) => {
+    $(pub fn $fn_name(slice: &[u8], field_name: &str) -> Result<[u8; $n], BindingError> {
         if slice.len() != $n {
-            panic!(
-                "Provided value for '{}' is not of expected size of {} bytes. Instead got {} bytes.",
-                field_name,
-                $n,
-                slice.len()
-            );
+            return Err(BindingError::InputWrongSize {
+                field: field_name.to_string(),
+                expected: $n,
+                actual: slice.len(),
+            });
         }
         let mut fixed_length: [u8; $n] = [0; $n];
         fixed_length.copy_from_slice(slice);
-        fixed_length
+        Ok(fixed_length)
     })+
 }}
 
 // Use the macro to generate the various fixed length array types
 slice_to_fixed_bytes! {
-    slice_to_fixed_32_bytes, &[u8], 32;
-    slice_to_fixed_64_bytes, &[u8], 64;
-    slice_to_fixed_384_bytes, &[u8], 384;
-    vector_to_fixed_32_bytes, &Vec<u8>, 32;
-    vector_to_fixed_64_bytes, &Vec<u8>, 64;
-    vector_to_fixed_128_bytes, &Vec<u8>, 128;
-    vector_to_fixed_384_bytes, &Vec<u8>, 384
+    slice_to_fixed_12_bytes, 12;
+    slice_to_fixed_16_bytes, 16;
+    slice_to_fixed_32_bytes, 32;
+    slice_to_fixed_33_bytes, 33;
+    slice_to_fixed_64_bytes, 64;
+    slice_to_fixed_128_bytes, 128;
+    slice_to_fixed_384_bytes, 384
+}
+
+macro_rules! js_to_fixed_bytes { ($($fn_name: ident, $slice_fn_name: ident, $n: expr); *) => {
+    $(pub fn $fn_name(value: &JsValue, field_name: &str) -> Result<[u8; $n], BindingError> {
+        $slice_fn_name(&Zeroizing::new(js_value_to_bytes(value, field_name)?), field_name)
+    })+
+}}
+
+// Same as above, but for byte parameters passed directly from JS in any of the forms js_value_to_bytes supports
+js_to_fixed_bytes! {
+    js_to_fixed_12_bytes, slice_to_fixed_12_bytes, 12;
+    js_to_fixed_32_bytes, slice_to_fixed_32_bytes, 32;
+    js_to_fixed_33_bytes, slice_to_fixed_33_bytes, 33;
+    js_to_fixed_64_bytes, slice_to_fixed_64_bytes, 64;
+    js_to_fixed_384_bytes, slice_to_fixed_384_bytes, 384
+}
+
+/**
+ * Convert the provided private signing key into a SigningKeypair. Accepts either a 32 byte Ed25519 seed, which is expanded
+ * into the 64 byte form, or the 64 byte seed and public key form, in which case the public half must match the seed.
+ * Wrong sizes are reported against the 64 byte form.
+ */
+pub fn slice_to_signing_keypair(slice: &[u8], field: &str) -> Result<SigningKeypair, JsError> {
+    if slice.len() == 32 {
+        return seed_to_signing_keypair(&slice_to_fixed_32_bytes(slice, field)?, field);
+    }
+    SigningKeypair::from_bytes(&slice_to_fixed_64_bytes(slice, field)?).map_err(|_| {
+        BindingError::InvalidSigningKeyPair {
+            field: field.to_string(),
+            message: format!(
+                "The last 32 bytes of '{}' are not the public key of the Ed25519 seed in its first 32 bytes.",
+                field
+            ),
+        }
+        .into()
+    })
+}
+
+/**
+ * Expand a 32 byte Ed25519 seed into the 64 byte seed and public key form used by recrypt.
+ */
+pub fn seed_to_signing_keypair(seed: &[u8; 32], field: &str) -> Result<SigningKeypair, JsError> {
+    let mut key_pair_bytes = Zeroizing::new([0u8; 64]);
+    key_pair_bytes[..32].copy_from_slice(seed);
+    key_pair_bytes[32..].copy_from_slice(
+        ed25519_dalek::SigningKey::from_bytes(seed)
+            .verifying_key()
+            .as_bytes(),
+    );
+    slice_to_signing_keypair(key_pair_bytes.as_slice(), field)
+}
+
+/**
+ * The 32 byte Ed25519 seed of the provided SigningKeypair, which is what most other libraries store as the private key.
+ */
+pub fn signing_keypair_seed(signing_key_pair: &SigningKeypair) -> Zeroizing<[u8; 32]> {
+    let mut seed = Zeroizing::new([0u8; 32]);
+    seed.copy_from_slice(&signing_key_pair.bytes()[..32]);
+    seed
+}
+
+/**
+ * Same as slice_to_signing_keypair, but for a signing key passed directly from JS.
+ */
+pub fn js_to_signing_keypair(value: &JsValue, field: &str) -> Result<SigningKeypair, JsError> {
+    slice_to_signing_keypair(&Zeroizing::new(js_value_to_bytes(value, field)?), field)
 }
 
 /**
@@ -115,102 +827,392 @@ pub fn public_key_to_js_object(public_key: PublicKey) -> JsPublicKey {
 }
 
 /**
- * Convert a public and private keypair into a serializable KeyPair struct which will be exported to JS
+ * Convert a public and private keypair into a JsKeyPair struct which will be exported to JS
  */
 pub fn key_pair_to_js_object(private_key: PrivateKey, public_key: PublicKey) -> JsKeyPair {
     JsKeyPair {
-        privateKey: *private_key.bytes(),
+        privateKey: Zeroizing::new(*private_key.bytes()),
         publicKey: public_key_to_js_object(public_key),
     }
 }
 
 /**
- * Convert a signing key pair into a serializable JsSigningKeyPair struct which will be exported to JS
+ * Convert a signing key pair into a JsSigningKeyPair struct which will be exported to JS
  */
 pub fn signing_keys_to_js_object(signing_key_pair: SigningKeypair) -> JsSigningKeyPair {
     JsSigningKeyPair {
-        privateKey: signing_key_pair.bytes().to_vec(),
+        privateKey: Zeroizing::new(signing_key_pair.bytes().to_vec()),
         publicKey: signing_key_pair.public_key().bytes().to_vec(),
     }
 }
 
 /**
- * Convert a JsPublicKey object into an internal Recrypt PublicKey
+ * Ensure the provided value is a JS object so its properties can be read. The field is the path to the object.
  */
-pub fn js_object_to_public_key(public_key_obj: &JsPublicKey) -> Result<PublicKey, JsError> {
-    Ok(PublicKey::new((
-        vector_to_fixed_32_bytes(&public_key_obj.x, "publicKey.x"),
-        vector_to_fixed_32_bytes(&public_key_obj.y, "publicKey.y"),
-    ))
-    .map_err(WasmError::new)?)
-}
-
-/**
- * Convert an incoming JsTransformKey from the JS struct into an internal TransformKey instance
- */
-pub fn js_object_to_transform_key(js_object: JsTransformKey) -> Result<TransformKey, JsError> {
-    Ok(TransformKey::new(
-        js_object_to_public_key(&js_object.ephemeralPublicKey)?,
-        js_object_to_public_key(&js_object.toPublicKey)?,
-        EncryptedTempKey::new(vector_to_fixed_384_bytes(
-            &js_object.encryptedTempKey,
-            "encryptedTempKey",
-        )),
-        HashedValue::new(vector_to_fixed_128_bytes(
-            &js_object.hashedTempKey,
-            "hashedTempKey",
-        ))
-        .map_err(WasmError::new)?,
-        PublicSigningKey::new(vector_to_fixed_32_bytes(
-            &js_object.publicSigningKey,
-            "publicSigningKey",
-        )),
-        Ed25519Signature::new(vector_to_fixed_64_bytes(&js_object.signature, "signature")),
+fn expect_js_object(value: &JsValue, field: &str) -> Result<(), BindingError> {
+    if value.is_object() {
+        Ok(())
+    } else {
+        Err(BindingError::InvalidType {
+            field: field.to_string(),
+            expected: "an object",
+        })
+    }
+}
+
+/**
+ * Read the bytes property with the provided name off of the provided JS object and convert it into a fixed length array
+ * using the provided conversion function.
+ */
+fn check_fixed_bytes_property<const N: usize>(
+    object: &JsValue,
+    name: &str,
+    field: &str,
+    to_fixed_bytes: fn(&[u8], &str) -> Result<[u8; N], BindingError>,
+    diagnostics: &mut Diagnostics,
+) -> Option<[u8; N]> {
+    let property_field = format!("{}.{}", field, name);
+    let value = diagnostics.check(get_js_property(object, name, field))?;
+    let bytes = diagnostics.check(js_value_to_bytes(&value, &property_field))?;
+    diagnostics.check(to_fixed_bytes(&bytes, &property_field))
+}
+
+/**
+ * Check a public key object with x/y properties, or a 33 byte compressed public key, and convert it into an internal Recrypt
+ * PublicKey. The field is the path to the public key within the value provided from JS and is used to report which value
+ * was invalid.
+ */
+fn check_public_key(
+    value: &JsValue,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<PublicKey> {
+    // Byte values are compressed public keys, anything else has to be an object with x/y properties
+    if js_value_to_uint8_array(value).is_some() || Array::is_array(value) {
+        let compressed = diagnostics.check(js_to_fixed_33_bytes(value, field))?;
+        return diagnostics.check(encoding::decompress_public_key(&compressed, field));
+    }
+    diagnostics.check(expect_js_object(value, field))?;
+    let x = check_fixed_bytes_property(value, "x", field, slice_to_fixed_32_bytes, diagnostics);
+    let y = check_fixed_bytes_property(value, "y", field, slice_to_fixed_32_bytes, diagnostics);
+    diagnostics.check(PublicKey::new((x?, y?)).map_err(|e| WasmError::with_field(e, field)))
+}
+
+/**
+ * Same as check_public_key, but for a public key which is stored in the property with the provided name.
+ */
+fn check_public_key_property(
+    object: &JsValue,
+    name: &str,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<PublicKey> {
+    let value = diagnostics.check(get_js_property(object, name, field))?;
+    check_public_key(&value, &format!("{}.{}", field, name), diagnostics)
+}
+
+/**
+ * Check a transform key object and convert it into an internal TransformKey instance.
+ */
+fn check_transform_key(
+    value: &JsValue,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<TransformKey> {
+    diagnostics.check(expect_js_object(value, field))?;
+    let ephemeral_public_key =
+        check_public_key_property(value, "ephemeralPublicKey", field, diagnostics);
+    let to_public_key = check_public_key_property(value, "toPublicKey", field, diagnostics);
+    let encrypted_temp_key = check_fixed_bytes_property(
+        value,
+        "encryptedTempKey",
+        field,
+        slice_to_fixed_384_bytes,
+        diagnostics,
+    );
+    let hashed_temp_key = check_fixed_bytes_property(
+        value,
+        "hashedTempKey",
+        field,
+        slice_to_fixed_128_bytes,
+        diagnostics,
+    )
+    .and_then(|bytes| {
+        diagnostics.check(
+            HashedValue::new(bytes)
+                .map_err(|e| WasmError::with_field(e, &format!("{}.hashedTempKey", field))),
+        )
+    });
+    let public_signing_key = check_fixed_bytes_property(
+        value,
+        "publicSigningKey",
+        field,
+        slice_to_fixed_32_bytes,
+        diagnostics,
+    );
+    let signature = check_fixed_bytes_property(
+        value,
+        "signature",
+        field,
+        slice_to_fixed_64_bytes,
+        diagnostics,
+    );
+
+    Some(TransformKey::new(
+        ephemeral_public_key?,
+        to_public_key?,
+        EncryptedTempKey::new(encrypted_temp_key?),
+        hashed_temp_key?,
+        PublicSigningKey::new(public_signing_key?),
+        Ed25519Signature::new(signature?),
     ))
 }
 
 /**
- * Convert a TransformKey struct into a serializable transform key struct which will be exported to JS
+ * Check a single transform block object and convert it into an internal TransformBlock.
  */
-pub fn transform_key_to_js_object(transform_key: TransformKey) -> JsTransformKey {
-    JsTransformKey {
-        toPublicKey: public_key_to_js_object(*transform_key.to_public_key()),
-        ephemeralPublicKey: public_key_to_js_object(*transform_key.ephemeral_public_key()),
-        encryptedTempKey: transform_key.encrypted_temp_key().bytes().to_vec(),
-        hashedTempKey: transform_key.hashed_temp_key().bytes().to_vec(),
-        publicSigningKey: transform_key.public_signing_key().bytes().to_vec(),
-        signature: transform_key.signature().bytes().to_vec(),
+fn check_transform_block(
+    value: &JsValue,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<TransformBlock> {
+    diagnostics.check(expect_js_object(value, field))?;
+    let public_key = check_public_key_property(value, "publicKey", field, diagnostics);
+    let encrypted_temp_key = check_fixed_bytes_property(
+        value,
+        "encryptedTempKey",
+        field,
+        slice_to_fixed_384_bytes,
+        diagnostics,
+    );
+    let random_transform_public_key =
+        check_public_key_property(value, "randomTransformPublicKey", field, diagnostics);
+    let random_transform_encrypted_temp_key = check_fixed_bytes_property(
+        value,
+        "randomTransformEncryptedTempKey",
+        field,
+        slice_to_fixed_384_bytes,
+        diagnostics,
+    );
+
+    diagnostics.check(
+        TransformBlock::new(
+            &public_key?,
+            &EncryptedTempKey::new(encrypted_temp_key?),
+            &random_transform_public_key?,
+            &EncryptedTempKey::new(random_transform_encrypted_temp_key?),
+        )
+        .map_err(|e| WasmError::with_field(e, field)),
+    )
+}
+
+/**
+ * Check an encrypted value object and convert it into an internal recrypt EncryptedValue instance. An empty list of
+ * transform blocks results in an EncryptedOnceValue.
+ */
+fn check_encrypted_value(
+    value: &JsValue,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<EncryptedValue> {
+    diagnostics.check(expect_js_object(value, field))?;
+    let ephemeral_public_key =
+        check_public_key_property(value, "ephemeralPublicKey", field, diagnostics);
+    let encrypted_message = check_fixed_bytes_property(
+        value,
+        "encryptedMessage",
+        field,
+        slice_to_fixed_384_bytes,
+        diagnostics,
+    );
+    let auth_hash = check_fixed_bytes_property(
+        value,
+        "authHash",
+        field,
+        slice_to_fixed_32_bytes,
+        diagnostics,
+    );
+    let public_signing_key = check_fixed_bytes_property(
+        value,
+        "publicSigningKey",
+        field,
+        slice_to_fixed_32_bytes,
+        diagnostics,
+    );
+    let signature = check_fixed_bytes_property(
+        value,
+        "signature",
+        field,
+        slice_to_fixed_64_bytes,
+        diagnostics,
+    );
+    let transform_blocks = check_transform_blocks_property(value, field, diagnostics);
+
+    let ephemeral_public_key = ephemeral_public_key?;
+    let encrypted_message = EncryptedMessage::new(encrypted_message?);
+    let auth_hash = AuthHash::new(auth_hash?);
+    let public_signing_key = PublicSigningKey::new(public_signing_key?);
+    let signature = Ed25519Signature::new(signature?);
+    let transform_blocks = transform_blocks?;
+
+    if transform_blocks.is_empty() {
+        Some(EncryptedValue::EncryptedOnceValue {
+            ephemeral_public_key,
+            encrypted_message,
+            auth_hash,
+            public_signing_key,
+            signature,
+        })
+    } else {
+        Some(EncryptedValue::TransformedValue {
+            ephemeral_public_key,
+            encrypted_message,
+            auth_hash,
+            public_signing_key,
+            signature,
+            transform_blocks: diagnostics
+                .check(NonEmptyVec::try_from(&transform_blocks).map_err(WasmError::new))?,
+        })
     }
 }
 
 /**
- * Convert an array of JsTransformBlocks into a non-empty vector of internal recrypt TransformBlock structs
+ * Check the transformBlocks array of an encrypted value object. Every block is checked, even after an invalid one, so
+ * that all problems are reported.
  */
-pub fn js_object_to_transform_blocks(
-    js_object: Vec<JsTransformBlock>,
-) -> Result<NonEmptyVec<TransformBlock>, JsError> {
-    let blocks: Result<Vec<TransformBlock>, JsError> = js_object
+fn check_transform_blocks_property(
+    object: &JsValue,
+    field: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<Vec<TransformBlock>> {
+    let blocks_field = format!("{}.transformBlocks", field);
+    let blocks = diagnostics.check(get_js_property(object, "transformBlocks", field))?;
+    let blocks =
+        diagnostics.check(
+            blocks
+                .dyn_into::<Array>()
+                .map_err(|_| BindingError::InvalidType {
+                    field: blocks_field.clone(),
+                    expected: "an Array",
+                }),
+        )?;
+    let checked_blocks: Vec<Option<TransformBlock>> = blocks
         .iter()
-        .map(|block| {
-            Ok(TransformBlock::new(
-                &js_object_to_public_key(&block.publicKey)?,
-                &EncryptedTempKey::new(vector_to_fixed_384_bytes(
-                    &block.encryptedTempKey,
-                    "transformBlock.encryptedTempKey",
-                )),
-                &js_object_to_public_key(&block.randomTransformPublicKey)?,
-                &EncryptedTempKey::new(vector_to_fixed_384_bytes(
-                    &block.randomTransformEncryptedTempKey,
-                    "transformBlock.randomTransformEncryptedTempKey",
-                )),
-            )
-            .map_err(WasmError::new)?)
+        .enumerate()
+        .map(|(index, block)| {
+            check_transform_block(&block, &format!("{}[{}]", blocks_field, index), diagnostics)
         })
         .collect();
+    checked_blocks.into_iter().collect()
+}
 
-    blocks.and_then(|transform_blocks| {
-        Ok(NonEmptyVec::try_from(&transform_blocks).map_err(WasmError::new)?)
-    })
+/**
+ * Convert a public key object provided from JS into an internal Recrypt PublicKey. Throws the first problem found with the
+ * value.
+ */
+pub fn js_object_to_public_key(value: &JsValue, field: &str) -> Result<PublicKey, JsError> {
+    Diagnostics::run(|diagnostics| check_public_key(value, field, diagnostics))
+}
+
+/**
+ * Convert a transform key object provided from JS into an internal TransformKey. Throws the first problem found with the
+ * value.
+ */
+pub fn js_object_to_transform_key(value: &JsValue, field: &str) -> Result<TransformKey, JsError> {
+    Diagnostics::run(|diagnostics| check_transform_key(value, field, diagnostics))
+}
+
+/**
+ * Convert an encrypted value object provided from JS into an internal EncryptedValue. Throws the first problem found with
+ * the value.
+ */
+pub fn js_object_to_encrypted_value(
+    value: &JsValue,
+    field: &str,
+) -> Result<EncryptedValue, JsError> {
+    Diagnostics::run(|diagnostics| check_encrypted_value(value, field, diagnostics))
+}
+
+/**
+ * Convert a key pair object with privateKey/publicKey properties provided from JS into an internal PrivateKey and
+ * PublicKey. Only the size of the private key and the validity of the public key are checked, not that they match.
+ */
+pub fn js_object_to_key_pair(
+    value: &JsValue,
+    field: &str,
+) -> Result<(PrivateKey, PublicKey), JsError> {
+    let private_key = js_to_fixed_32_bytes(
+        &get_js_property(value, "privateKey", field)?,
+        &format!("{}.privateKey", field),
+    )?;
+    let public_key = js_object_to_public_key(
+        &get_js_property(value, "publicKey", field)?,
+        &format!("{}.publicKey", field),
+    )?;
+    Ok((PrivateKey::new(private_key), public_key))
+}
+
+/**
+ * Convert a signing key pair object with privateKey/publicKey properties provided from JS into a SigningKeypair,
+ * verifying that the publicKey property matches the public half of the private key.
+ */
+pub fn js_object_to_signing_keypair(
+    value: &JsValue,
+    field: &str,
+) -> Result<SigningKeypair, JsError> {
+    let signing_key_pair = js_to_signing_keypair(
+        &get_js_property(value, "privateKey", field)?,
+        &format!("{}.privateKey", field),
+    )?;
+    let public_key_field = format!("{}.publicKey", field);
+    let public_key = js_to_fixed_32_bytes(
+        &get_js_property(value, "publicKey", field)?,
+        &public_key_field,
+    )?;
+    if &public_key != signing_key_pair.public_key().bytes() {
+        return Err(BindingError::InvalidSigningKeyPair {
+            field: public_key_field,
+            message: "The public key does not match the public half of the private signing key."
+                .to_string(),
+        }
+        .into());
+    }
+    Ok(signing_key_pair)
+}
+
+/**
+ * Run the same checks as js_object_to_public_key, but return every problem found instead of throwing the first.
+ */
+pub fn validate_public_key(value: &JsValue, field: &str) -> Diagnostics {
+    Diagnostics::collect(|diagnostics| check_public_key(value, field, diagnostics))
+}
+
+/**
+ * Run the same checks as js_object_to_transform_key, but return every problem found instead of throwing the first.
+ */
+pub fn validate_transform_key(value: &JsValue, field: &str) -> Diagnostics {
+    Diagnostics::collect(|diagnostics| check_transform_key(value, field, diagnostics))
+}
+
+/**
+ * Run the same checks as js_object_to_encrypted_value, but return every problem found instead of throwing the first.
+ */
+pub fn validate_encrypted_value(value: &JsValue, field: &str) -> Diagnostics {
+    Diagnostics::collect(|diagnostics| check_encrypted_value(value, field, diagnostics))
+}
+
+/**
+ * Convert a TransformKey struct into a JsTransformKey struct which will be exported to JS
+ */
+pub fn transform_key_to_js_object(transform_key: TransformKey) -> JsTransformKey {
+    JsTransformKey {
+        toPublicKey: public_key_to_js_object(*transform_key.to_public_key()),
+        ephemeralPublicKey: public_key_to_js_object(*transform_key.ephemeral_public_key()),
+        encryptedTempKey: transform_key.encrypted_temp_key().bytes().to_vec(),
+        hashedTempKey: transform_key.hashed_temp_key().bytes().to_vec(),
+        publicSigningKey: transform_key.public_signing_key().bytes().to_vec(),
+        signature: transform_key.signature().bytes().to_vec(),
+    }
 }
 
 /**
@@ -233,49 +1235,65 @@ pub fn transform_blocks_to_js_object(
         .collect()
 }
 
+pub struct JsEncryptedValueInfo {
+    pub variant: &'static str,
+    pub transformLevel: usize,
+    pub transformedWith: Vec<JsPublicKey>,
+    pub publicSigningKey: Vec<u8>,
+}
+
+impl From<JsEncryptedValueInfo> for JsValue {
+    fn from(info: JsEncryptedValueInfo) -> JsValue {
+        let transformed_with: Array = info
+            .transformedWith
+            .into_iter()
+            .map(JsValue::from)
+            .collect();
+        new_js_object(&[
+            ("variant", info.variant.into()),
+            ("transformLevel", info.transformLevel.into()),
+            ("transformedWith", transformed_with.into()),
+            ("publicSigningKey", bytes_to_js(&info.publicSigningKey)),
+        ])
+    }
+}
+
 /**
- * Convert an incoming JsEncryptedValue into an internal recrypt EncryptedValue instance
+ * Describe the provided EncryptedValue without decrypting it: which variant it is, how many times it has been transformed,
+ * the transform key used for each hop (in the order the transforms happened) and the public signing key that signed the
+ * value. Recrypt doesn't store the public key a value was transformed to. Each transform block instead holds the
+ * ephemeral public key of the transform key that produced it, which identifies that transform key and through it the
+ * delegatee.
  */
-pub fn js_object_to_encrypted_value(
-    js_object: JsEncryptedValue,
-) -> Result<EncryptedValue, JsError> {
-    let ephemeral_public_key = js_object_to_public_key(&js_object.ephemeralPublicKey)?;
-    let encrypted_message = EncryptedMessage::new(vector_to_fixed_384_bytes(
-        &js_object.encryptedMessage,
-        "encryptedMessage",
-    ));
-    let auth_hash = AuthHash::new(vector_to_fixed_32_bytes(&js_object.authHash, "authHash"));
-    let public_signing_key = PublicSigningKey::new(vector_to_fixed_32_bytes(
-        &js_object.publicSigningKey,
-        "publicSigningKey",
-    ));
-    let signature =
-        Ed25519Signature::new(vector_to_fixed_64_bytes(&js_object.signature, "signature"));
-
-    let encrypted_value = if !js_object.transformBlocks.is_empty() {
-        let transform_blocks = js_object_to_transform_blocks(js_object.transformBlocks)?;
+pub fn encrypted_value_to_js_info(encrypted_value: &EncryptedValue) -> JsEncryptedValueInfo {
+    match encrypted_value {
+        EncryptedValue::EncryptedOnceValue {
+            public_signing_key, ..
+        } => JsEncryptedValueInfo {
+            variant: "EncryptedOnceValue",
+            transformLevel: 0,
+            transformedWith: vec![],
+            publicSigningKey: public_signing_key.bytes().to_vec(),
+        },
         EncryptedValue::TransformedValue {
-            ephemeral_public_key,
-            encrypted_message,
-            auth_hash,
             public_signing_key,
-            signature,
             transform_blocks,
-        }
-    } else {
-        EncryptedValue::EncryptedOnceValue {
-            ephemeral_public_key,
-            encrypted_message,
-            auth_hash,
-            public_signing_key,
-            signature,
-        }
-    };
-    Ok(encrypted_value)
+            ..
+        } => JsEncryptedValueInfo {
+            variant: "TransformedValue",
+            transformLevel: transform_blocks.len(),
+            transformedWith: transform_blocks
+                .to_vec()
+                .iter()
+                .map(|block| public_key_to_js_object(*block.public_key()))
+                .collect(),
+            publicSigningKey: public_signing_key.bytes().to_vec(),
+        },
+    }
 }
 
 /**
- * Convert an EncryptedValue sturct into a serializable JS transform key struct which will be exported to JS
+ * Convert an EncryptedValue struct into a JsEncryptedValue struct which will be exported to JS
  */
 pub fn encrypted_value_to_js_object(encrypted_value: EncryptedValue) -> JsEncryptedValue {
     let encrypted_value_tuple = match encrypted_value {
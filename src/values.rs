@@ -0,0 +1,207 @@
+#![allow(non_snake_case)]
+
+use crate::util::{self, JsError};
+use recrypt::api::{EncryptedValue, PublicKey, TransformKey};
+use wasm_bindgen::prelude::*;
+
+/**
+ * A Recrypt public key which has already been validated to be a point on the curve. Can be passed to the `*Wasm` methods
+ * on Api256 in place of a public key object to avoid converting and validating the key on every call.
+ */
+#[wasm_bindgen]
+pub struct WasmPublicKey {
+    pub(crate) key: PublicKey,
+}
+
+#[wasm_bindgen]
+impl WasmPublicKey {
+    /**
+     * Validate the provided public key object and convert it into a WasmPublicKey.
+     */
+    pub fn fromObject(public_key_obj: &JsValue) -> Result<WasmPublicKey, JsError> {
+        Ok(WasmPublicKey {
+            key: util::js_object_to_public_key(public_key_obj, "publicKey")?,
+        })
+    }
+
+    /**
+     * Convert this public key into a public key object with x/y Uint8Array properties.
+     */
+    pub fn toObject(&self) -> JsValue {
+        util::public_key_to_js_object(self.key).into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> Vec<u8> {
+        self.key.bytes_x_y().0.to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> Vec<u8> {
+        self.key.bytes_x_y().1.to_vec()
+    }
+}
+
+/**
+ * A Recrypt transform key whose public keys and hashed temp key have already been validated.
+ */
+#[wasm_bindgen]
+pub struct WasmTransformKey {
+    pub(crate) key: TransformKey,
+}
+
+#[wasm_bindgen]
+impl WasmTransformKey {
+    /**
+     * Validate the provided transform key object and convert it into a WasmTransformKey.
+     */
+    pub fn fromObject(transform_key_obj: &JsValue) -> Result<WasmTransformKey, JsError> {
+        Ok(WasmTransformKey {
+            key: util::js_object_to_transform_key(transform_key_obj, "transformKey")?,
+        })
+    }
+
+    /**
+     * Convert this transform key into a transform key object with Uint8Array properties.
+     */
+    pub fn toObject(&self) -> JsValue {
+        util::transform_key_to_js_object(self.key.clone()).into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ephemeralPublicKey(&self) -> WasmPublicKey {
+        WasmPublicKey {
+            key: *self.key.ephemeral_public_key(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn toPublicKey(&self) -> WasmPublicKey {
+        WasmPublicKey {
+            key: *self.key.to_public_key(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn encryptedTempKey(&self) -> Vec<u8> {
+        self.key.encrypted_temp_key().bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hashedTempKey(&self) -> Vec<u8> {
+        self.key.hashed_temp_key().bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn publicSigningKey(&self) -> Vec<u8> {
+        self.key.public_signing_key().bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signature(&self) -> Vec<u8> {
+        self.key.signature().bytes().to_vec()
+    }
+}
+
+/**
+ * A Recrypt encrypted value, either encrypted once or transformed one or more times, whose public keys and transform
+ * blocks have already been validated.
+ */
+#[wasm_bindgen]
+pub struct WasmEncryptedValue {
+    pub(crate) value: EncryptedValue,
+}
+
+#[wasm_bindgen]
+impl WasmEncryptedValue {
+    /**
+     * Validate the provided encrypted value object and convert it into a WasmEncryptedValue.
+     */
+    pub fn fromObject(encrypted_value_obj: &JsValue) -> Result<WasmEncryptedValue, JsError> {
+        Ok(WasmEncryptedValue {
+            value: util::js_object_to_encrypted_value(encrypted_value_obj, "encryptedValue")?,
+        })
+    }
+
+    /**
+     * Convert this encrypted value into an encrypted value object with Uint8Array properties.
+     */
+    pub fn toObject(&self) -> JsValue {
+        util::encrypted_value_to_js_object(self.value.clone()).into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ephemeralPublicKey(&self) -> WasmPublicKey {
+        let (EncryptedValue::EncryptedOnceValue {
+            ephemeral_public_key,
+            ..
+        }
+        | EncryptedValue::TransformedValue {
+            ephemeral_public_key,
+            ..
+        }) = &self.value;
+        WasmPublicKey {
+            key: *ephemeral_public_key,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn encryptedMessage(&self) -> Vec<u8> {
+        let (EncryptedValue::EncryptedOnceValue {
+            encrypted_message, ..
+        }
+        | EncryptedValue::TransformedValue {
+            encrypted_message, ..
+        }) = &self.value;
+        encrypted_message.bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn authHash(&self) -> Vec<u8> {
+        let (EncryptedValue::EncryptedOnceValue { auth_hash, .. }
+        | EncryptedValue::TransformedValue { auth_hash, .. }) = &self.value;
+        auth_hash.bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn publicSigningKey(&self) -> Vec<u8> {
+        let (EncryptedValue::EncryptedOnceValue {
+            public_signing_key, ..
+        }
+        | EncryptedValue::TransformedValue {
+            public_signing_key, ..
+        }) = &self.value;
+        public_signing_key.bytes().to_vec()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signature(&self) -> Vec<u8> {
+        let (EncryptedValue::EncryptedOnceValue { signature, .. }
+        | EncryptedValue::TransformedValue { signature, .. }) = &self.value;
+        signature.bytes().to_vec()
+    }
+
+    /**
+     * Describe this value without decrypting it. See inspectEncryptedValue.
+     */
+    pub fn inspect(&self) -> JsValue {
+        util::encrypted_value_to_js_info(&self.value).into()
+    }
+
+    /**
+     * The transform blocks of this value as an array of transform block objects. Empty if the value has not been
+     * transformed.
+     */
+    #[wasm_bindgen(getter)]
+    pub fn transformBlocks(&self) -> Vec<JsValue> {
+        match &self.value {
+            EncryptedValue::EncryptedOnceValue { .. } => vec![],
+            EncryptedValue::TransformedValue {
+                transform_blocks, ..
+            } => util::transform_blocks_to_js_object(transform_blocks.to_vec())
+                .into_iter()
+                .map(JsValue::from)
+                .collect(),
+        }
+    }
+}